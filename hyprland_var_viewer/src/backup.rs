@@ -0,0 +1,233 @@
+// Safe writing of config files and the snapshots taken before every save
+
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Name of the file in each snapshot that lists the saved files
+const MANIFEST: &str = "manifest";
+
+/// A copy of config files taken before they were overwritten
+#[derive(Debug)]
+pub struct Snapshot {
+    /// Local time the snapshot was taken, also the name of its directory
+    pub id: String,
+    pub path: PathBuf,
+    /// Saved files as (name inside the snapshot, path relative to the config directory)
+    pub files: Vec<(String, String)>,
+}
+
+/// Hash of a file's content, used to notice when a file changed on disk
+pub fn content_hash(content: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Directory holding the snapshots, usually ~/.cache/hyprland-settings
+pub fn backup_dir() -> PathBuf {
+    glib::user_cache_dir().join("hyprland-settings")
+}
+
+/// Replace a file by writing a temporary file next to it and renaming it over
+/// the original, so the file is never left half written. Symlinks are followed
+/// so the link itself stays in place.
+pub fn write_atomically(path: &Path, content: &str) -> Result<(), String> {
+    let target = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let file_name = target
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .ok_or_else(|| format!("{} is not a file", path.display()))?;
+    let temp_path = target.with_file_name(format!(".{}.hyprland-settings.tmp", file_name));
+    
+    let write = || -> std::io::Result<()> {
+        let mut file = fs::File::create(&temp_path)?;
+        file.write_all(content.as_bytes())?;
+        file.sync_all()?;
+        
+        // Keep the permissions of the file being replaced
+        if let Ok(metadata) = fs::metadata(&target) {
+            fs::set_permissions(&temp_path, metadata.permissions())?;
+        }
+        fs::rename(&temp_path, &target)
+    };
+    
+    write().map_err(|e| {
+        let _ = fs::remove_file(&temp_path);
+        format!("Failed to write {}: {}", path.display(), e)
+    })
+}
+
+/// Copy config files (given relative to `base_dir`) into a new snapshot
+pub fn create_snapshot(base_dir: &Path, files: &[String]) -> Result<Snapshot, String> {
+    let now = glib::DateTime::now_local().map_err(|e| format!("Failed to read the time: {}", e))?;
+    let id = now
+        .format("%Y-%m-%d %H:%M:%S")
+        .map_err(|e| format!("Failed to format the time: {}", e))?
+        .to_string();
+    
+    // Several saves within a second get their own snapshot
+    let mut path = backup_dir().join(&id);
+    let mut counter = 1;
+    while path.exists() {
+        counter += 1;
+        path = backup_dir().join(format!("{} ({})", id, counter));
+    }
+    fs::create_dir_all(&path).map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+    
+    let mut snapshot = Snapshot {
+        id: path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or(id),
+        path,
+        files: Vec::new(),
+    };
+    
+    for (index, file) in files.iter().enumerate() {
+        let source = base_dir.join(file);
+        let file_name = source.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+        let stored_name = format!("{}-{}", index, file_name);
+        
+        fs::copy(&source, snapshot.path.join(&stored_name))
+            .map_err(|e| format!("Failed to back up {}: {}", file, e))?;
+        snapshot.files.push((stored_name, file.clone()));
+    }
+    
+    let manifest: String = snapshot.files
+        .iter()
+        .map(|(stored_name, file)| format!("{}\t{}\n", stored_name, file))
+        .collect();
+    fs::write(snapshot.path.join(MANIFEST), manifest)
+        .map_err(|e| format!("Failed to write the backup manifest: {}", e))?;
+    
+    Ok(snapshot)
+}
+
+/// List the snapshots, newest first
+pub fn list_snapshots() -> Vec<Snapshot> {
+    let entries = match fs::read_dir(backup_dir()) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+    
+    let mut snapshots: Vec<(std::time::SystemTime, Snapshot)> = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let path = entry.path();
+            let manifest = fs::read_to_string(path.join(MANIFEST)).ok()?;
+            let files = manifest
+                .lines()
+                .filter_map(|line| line.split_once('\t'))
+                .map(|(stored_name, file)| (stored_name.to_string(), file.to_string()))
+                .collect();
+            let modified = entry.metadata().and_then(|metadata| metadata.modified()).ok()?;
+            
+            Some((modified, Snapshot {
+                id: entry.file_name().to_string_lossy().to_string(),
+                path,
+                files,
+            }))
+        })
+        .collect();
+    
+    snapshots.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+    snapshots.into_iter().map(|(_, snapshot)| snapshot).collect()
+}
+
+/// Put the files of a snapshot back in place. The current files are saved in a
+/// new snapshot first, which is returned so the restore can be undone too.
+pub fn restore_snapshot(snapshot: &Snapshot, base_dir: &Path) -> Result<Snapshot, String> {
+    let existing: Vec<String> = snapshot.files
+        .iter()
+        .map(|(_, file)| file.clone())
+        .filter(|file| base_dir.join(file).exists())
+        .collect();
+    let backup = create_snapshot(base_dir, &existing)?;
+    
+    for (stored_name, file) in &snapshot.files {
+        let content = fs::read_to_string(snapshot.path.join(stored_name))
+            .map_err(|e| format!("Failed to read the backup of {}: {}", file, e))?;
+        write_atomically(&base_dir.join(file), &content)?;
+    }
+    
+    Ok(backup)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::os::unix::fs::{symlink, PermissionsExt};
+    use std::sync::OnceLock;
+    
+    /// Keep the snapshots of all tests in one temporary cache directory. GLib
+    /// reads XDG_CACHE_HOME once, so it is set before the first snapshot.
+    pub(crate) fn use_test_cache_dir() {
+        static CACHE_DIR: OnceLock<PathBuf> = OnceLock::new();
+        CACHE_DIR.get_or_init(|| {
+            let dir = std::env::temp_dir().join(format!("hyprland-settings-test-{}", std::process::id()));
+            std::env::set_var("XDG_CACHE_HOME", &dir);
+            assert_eq!(glib::user_cache_dir(), dir);
+            dir
+        });
+    }
+    
+    #[test]
+    fn write_keeps_symlinks_and_permissions() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("looks.conf");
+        let link = dir.path().join("hyprland.conf");
+        fs::write(&target, "general {\n    gaps_in = 5\n}\n").unwrap();
+        fs::set_permissions(&target, fs::Permissions::from_mode(0o600)).unwrap();
+        symlink(&target, &link).unwrap();
+        
+        write_atomically(&link, "general {\n    gaps_in = 8\n}\n").unwrap();
+        
+        assert!(fs::symlink_metadata(&link).unwrap().file_type().is_symlink());
+        assert_eq!(fs::read_to_string(&target).unwrap(), "general {\n    gaps_in = 8\n}\n");
+        assert_eq!(fs::metadata(&target).unwrap().permissions().mode() & 0o777, 0o600);
+        // No temporary file is left behind
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 2);
+    }
+    
+    #[test]
+    fn snapshots_taken_together_get_their_own_ids() {
+        use_test_cache_dir();
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("hyprland.conf"), "general:gaps_in = 5\n").unwrap();
+        let files = vec!["hyprland.conf".to_string()];
+        
+        let first = create_snapshot(dir.path(), &files).unwrap();
+        let second = create_snapshot(dir.path(), &files).unwrap();
+        assert_ne!(first.id, second.id);
+        assert_ne!(first.path, second.path);
+    }
+    
+    #[test]
+    fn restore_puts_back_files_and_backs_up_the_current_ones() {
+        use_test_cache_dir();
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("conf")).unwrap();
+        fs::write(dir.path().join("hyprland.conf"), "source = conf/looks.conf\r\ngeneral:gaps_in = 5\r\n").unwrap();
+        fs::write(dir.path().join("conf/looks.conf"), "decoration {\n    rounding = 10\n}").unwrap();
+        let files = vec!["hyprland.conf".to_string(), "conf/looks.conf".to_string()];
+        
+        let snapshot = create_snapshot(dir.path(), &files).unwrap();
+        fs::write(dir.path().join("hyprland.conf"), "general:gaps_in = 8\n").unwrap();
+        fs::write(dir.path().join("conf/looks.conf"), "decoration {\n    rounding = 0\n}\n").unwrap();
+        
+        // Look the snapshot up the way the history window does
+        let listed = list_snapshots().into_iter().find(|listed| listed.path == snapshot.path).unwrap();
+        assert_eq!(listed.files, snapshot.files);
+        let backup = restore_snapshot(&listed, dir.path()).unwrap();
+        
+        assert_eq!(
+            fs::read_to_string(dir.path().join("hyprland.conf")).unwrap(),
+            "source = conf/looks.conf\r\ngeneral:gaps_in = 5\r\n"
+        );
+        assert_eq!(fs::read_to_string(dir.path().join("conf/looks.conf")).unwrap(), "decoration {\n    rounding = 10\n}");
+        
+        // The replaced files can be restored in turn
+        restore_snapshot(&backup, dir.path()).unwrap();
+        assert_eq!(fs::read_to_string(dir.path().join("hyprland.conf")).unwrap(), "general:gaps_in = 8\n");
+    }
+}
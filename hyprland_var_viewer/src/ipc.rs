@@ -0,0 +1,344 @@
+// Talking to the running Hyprland instance over its request socket
+
+use std::io::{Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Options read per `[[BATCH]]` request, which keeps each request small
+const OPTIONS_PER_BATCH: usize = 40;
+
+/// Separates the replies of a `[[BATCH]]` request
+const BATCH_DELIMITER: &str = "\n\n\n";
+
+/// Client for the Hyprland request socket (`.socket.sock`)
+pub struct HyprlandIpc {
+    socket_path: PathBuf,
+}
+
+impl HyprlandIpc {
+    /// Create a client for a specific socket, e.g. a fake socket for testing
+    pub fn new(socket_path: impl Into<PathBuf>) -> Self {
+        Self {
+            socket_path: socket_path.into(),
+        }
+    }
+    
+    /// Locate the socket of the running Hyprland instance. HYPRLAND_SETTINGS_SOCKET
+    /// overrides the location, so the app can be pointed at a fake socket.
+    pub fn from_env() -> Result<Self, String> {
+        if let Ok(path) = std::env::var("HYPRLAND_SETTINGS_SOCKET") {
+            return Ok(Self::new(path));
+        }
+        
+        let signature = std::env::var("HYPRLAND_INSTANCE_SIGNATURE")
+            .map_err(|_| "Hyprland is not running (HYPRLAND_INSTANCE_SIGNATURE is not set)".to_string())?;
+        
+        // Newer Hyprland versions use XDG_RUNTIME_DIR, older ones /tmp/hypr
+        let mut candidates = Vec::new();
+        if let Ok(runtime_dir) = std::env::var("XDG_RUNTIME_DIR") {
+            candidates.push(PathBuf::from(runtime_dir).join("hypr").join(&signature).join(".socket.sock"));
+        }
+        candidates.push(PathBuf::from("/tmp/hypr").join(&signature).join(".socket.sock"));
+        
+        candidates
+            .into_iter()
+            .find(|path| path.exists())
+            .map(Self::new)
+            .ok_or_else(|| "Could not find the Hyprland socket, is Hyprland running?".to_string())
+    }
+    
+    /// Send a raw request (e.g. `j/getoption general:gaps_in`) and return the reply
+    pub fn request(&self, command: &str) -> Result<String, String> {
+        let mut stream = UnixStream::connect(&self.socket_path)
+            .map_err(|e| format!("Failed to connect to {}: {}", self.socket_path.display(), e))?;
+        let _ = stream.set_read_timeout(Some(Duration::from_secs(2)));
+        let _ = stream.set_write_timeout(Some(Duration::from_secs(2)));
+        
+        stream.write_all(command.as_bytes())
+            .map_err(|e| format!("Failed to send '{}' to Hyprland: {}", command, e))?;
+        
+        let mut reply = String::new();
+        stream.read_to_string(&mut reply)
+            .map_err(|e| format!("Failed to read the reply to '{}': {}", command, e))?;
+        
+        Ok(reply)
+    }
+    
+    /// Set an option in the running compositor, e.g. `keyword general:gaps_in 10`
+    pub fn keyword(&self, option: &str, value: &str) -> Result<(), String> {
+        let reply = self.request(&format!("keyword {} {}", option, value))?;
+        if reply.trim() != "ok" {
+            return Err(format!("Hyprland rejected {} = {}: {}", option, value, reply.trim()));
+        }
+        Ok(())
+    }
+    
+    /// Read an option as reported by `getoption -j`
+    pub fn option_json(&self, option: &str) -> Result<serde_json::Value, String> {
+        let reply = self.request(&format!("j/getoption {}", option))?;
+        serde_json::from_str(&reply).map_err(|_| format!("Hyprland does not know {}: {}", option, reply.trim()))
+    }
+    
+    /// Read several options with `[[BATCH]]` requests instead of one request per
+    /// option. Options Hyprland does not know are `None`.
+    pub fn options_json(&self, options: &[&str]) -> Result<Vec<Option<serde_json::Value>>, String> {
+        let mut values = Vec::with_capacity(options.len());
+        
+        for chunk in options.chunks(OPTIONS_PER_BATCH) {
+            let commands: Vec<String> = chunk.iter().map(|option| format!("j/getoption {}", option)).collect();
+            let reply = self.request(&format!("[[BATCH]]{}", commands.join(";")))?;
+            
+            // Each reply is either the JSON of the option or an error message
+            let replies: Vec<&str> = reply.split(BATCH_DELIMITER).collect();
+            if replies.len() == chunk.len() {
+                values.extend(replies.iter().map(|reply| serde_json::from_str(reply).ok()));
+                continue;
+            }
+            
+            // Older Hyprland versions join the replies without a delimiter
+            let stream: Result<Vec<serde_json::Value>, _> = serde_json::Deserializer::from_str(&reply).into_iter().collect();
+            match stream {
+                Ok(stream) if stream.len() == chunk.len() => values.extend(stream.into_iter().map(Some)),
+                _ => return Err(format!("Could not read the options from Hyprland's reply: {}", reply.trim())),
+            }
+        }
+        
+        Ok(values)
+    }
+    
+    /// Read the current value of an option, written so it can be passed back to `keyword`
+    pub fn option_value(&self, option: &str) -> Result<String, String> {
+        let json = self.option_json(option)?;
+        keyword_value(&json).ok_or_else(|| format!("Could not read the value of {} from {}", option, json))
+    }
+}
+
+/// Turn a `getoption -j` reply into a value `keyword` accepts
+pub fn keyword_value(json: &serde_json::Value) -> Option<String> {
+    if let Some(number) = json.get("int").and_then(|v| v.as_i64()) {
+        return Some(number.to_string());
+    }
+    if let Some(number) = json.get("float").and_then(|v| v.as_f64()) {
+        return Some(number.to_string());
+    }
+    if let Some(text) = json.get("str").and_then(|v| v.as_str()) {
+        return Some(text.to_string());
+    }
+    if let Some(vec2) = json.get("vec2").and_then(|v| v.as_array()) {
+        let parts: Vec<String> = vec2.iter().filter_map(|v| v.as_f64()).map(|v| v.to_string()).collect();
+        return Some(parts.join(" "));
+    }
+    
+    // Gradients and css gaps are reported as custom values, e.g. `ee33ccff ee00ff99 45deg`
+    // and `5 10 5 10`, which need colour prefixes and commas to be read back
+    let custom = json.get("custom").and_then(|v| v.as_str())?.trim();
+    let tokens: Vec<&str> = custom.split_whitespace().collect();
+    // Colours are always 8 hex digits, even when they contain no letters
+    if tokens.iter().all(|token| token.len() < 8 && token.parse::<i64>().is_ok()) {
+        return Some(tokens.join(","));
+    }
+    let tokens: Vec<String> = tokens
+        .iter()
+        .map(|token| {
+            if token.len() == 8 && token.chars().all(|c| c.is_ascii_hexdigit()) {
+                format!("0x{}", token)
+            } else {
+                token.to_string()
+            }
+        })
+        .collect();
+    Some(tokens.join(" "))
+}
+
+/// Pushes edited values to the running compositor and remembers the runtime
+/// values from before the first edit of each option so they can be restored
+pub struct LivePreview {
+    ipc: HyprlandIpc,
+    captured: Vec<(String, String)>,
+}
+
+impl LivePreview {
+    pub fn new(ipc: HyprlandIpc) -> Self {
+        Self {
+            ipc,
+            captured: Vec::new(),
+        }
+    }
+    
+    /// Apply a value at runtime, capturing the current value first
+    pub fn apply(&mut self, option: &str, value: &str) -> Result<(), String> {
+        if !self.captured.iter().any(|(captured, _)| captured == option) {
+            let current = self.ipc.option_value(option)?;
+            self.captured.push((option.to_string(), current));
+        }
+        self.ipc.keyword(option, value)
+    }
+    
+    /// Whether any option was changed at runtime since the last revert or save
+    pub fn has_changes(&self) -> bool {
+        !self.captured.is_empty()
+    }
+    
+    /// Restore the captured runtime values, returning how many were restored
+    pub fn revert(&mut self) -> Result<usize, String> {
+        let mut errors = Vec::new();
+        let captured = std::mem::take(&mut self.captured);
+        
+        for (option, value) in &captured {
+            if let Err(err) = self.ipc.keyword(option, value) {
+                errors.push(err);
+            }
+        }
+        
+        if errors.is_empty() {
+            Ok(captured.len())
+        } else {
+            Err(errors.join("\n"))
+        }
+    }
+    
+    /// Forget the captured values once the edits are saved to the config
+    pub fn forget(&mut self) {
+        self.captured.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::os::unix::net::UnixListener;
+    use std::path::Path;
+    use std::thread::{self, JoinHandle};
+    
+    /// Serve one reply per connection on a socket at `path`, returning the requests received
+    fn fake_socket(path: &Path, replies: &[&str]) -> JoinHandle<Vec<String>> {
+        let listener = UnixListener::bind(path).unwrap();
+        let replies: Vec<String> = replies.iter().map(|reply| reply.to_string()).collect();
+        
+        thread::spawn(move || {
+            let mut requests = Vec::new();
+            for reply in replies {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut buffer = [0; 1024];
+                let len = stream.read(&mut buffer).unwrap();
+                requests.push(String::from_utf8_lossy(&buffer[..len]).to_string());
+                stream.write_all(reply.as_bytes()).unwrap();
+            }
+            requests
+        })
+    }
+    
+    #[test]
+    fn apply_captures_before_first_keyword() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join(".socket.sock");
+        let server = fake_socket(&socket, &[
+            r#"{"option": "general:gaps_in", "custom": "5 5 5 5", "set": true}"#,
+            "ok",
+            "ok",
+            r#"{"option": "decoration:rounding", "int": 10, "set": true}"#,
+            "ok",
+            "ok",
+            "ok",
+        ]);
+        
+        let mut preview = LivePreview::new(HyprlandIpc::new(&socket));
+        assert!(!preview.has_changes());
+        preview.apply("general:gaps_in", "8").unwrap();
+        // The second edit of an option keeps the value captured first
+        preview.apply("general:gaps_in", "12").unwrap();
+        preview.apply("decoration:rounding", "4").unwrap();
+        assert!(preview.has_changes());
+        assert_eq!(preview.revert().unwrap(), 2);
+        assert!(!preview.has_changes());
+        
+        assert_eq!(server.join().unwrap(), vec![
+            "j/getoption general:gaps_in",
+            "keyword general:gaps_in 8",
+            "keyword general:gaps_in 12",
+            "j/getoption decoration:rounding",
+            "keyword decoration:rounding 4",
+            "keyword general:gaps_in 5,5,5,5",
+            "keyword decoration:rounding 10",
+        ]);
+    }
+    
+    #[test]
+    fn unknown_option_is_not_applied() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join(".socket.sock");
+        let server = fake_socket(&socket, &["no such option"]);
+        
+        let mut preview = LivePreview::new(HyprlandIpc::new(&socket));
+        assert!(preview.apply("general:no_such_option", "1").is_err());
+        assert!(!preview.has_changes());
+        assert_eq!(server.join().unwrap(), vec!["j/getoption general:no_such_option"]);
+    }
+    
+    #[test]
+    fn rejected_keyword_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join(".socket.sock");
+        let server = fake_socket(&socket, &["invalid value"]);
+        
+        let err = HyprlandIpc::new(&socket).keyword("general:layout", "spiral").unwrap_err();
+        assert!(err.contains("invalid value"), "{}", err);
+        assert_eq!(server.join().unwrap(), vec!["keyword general:layout spiral"]);
+    }
+    
+    #[test]
+    fn reads_options_in_one_batch() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join(".socket.sock");
+        let server = fake_socket(&socket, &[
+            "{\n    \"option\": \"general:gaps_in\",\n    \"custom\": \"5 5 5 5\",\n    \"set\": true\n}\n\n\n\
+             no such option\n\n\n\
+             {\n    \"option\": \"decoration:rounding\",\n    \"int\": 10,\n    \"set\": true\n}",
+        ]);
+        
+        let values = HyprlandIpc::new(&socket)
+            .options_json(&["general:gaps_in", "plugin:missing:option", "decoration:rounding"])
+            .unwrap();
+        assert_eq!(server.join().unwrap(), vec![
+            "[[BATCH]]j/getoption general:gaps_in;j/getoption plugin:missing:option;j/getoption decoration:rounding",
+        ]);
+        let values: Vec<Option<String>> = values.iter().map(|json| json.as_ref().and_then(keyword_value)).collect();
+        assert_eq!(values, vec![Some("5,5,5,5".to_string()), None, Some("10".to_string())]);
+    }
+    
+    #[test]
+    fn reads_batch_replies_without_delimiter() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join(".socket.sock");
+        let server = fake_socket(&socket, &[r#"{"option": "a:b", "int": 1}{"option": "a:c", "float": 0.5}"#]);
+        
+        let values = HyprlandIpc::new(&socket).options_json(&["a:b", "a:c"]).unwrap();
+        assert_eq!(server.join().unwrap().len(), 1);
+        assert_eq!(values, vec![Some(json!({"option": "a:b", "int": 1})), Some(json!({"option": "a:c", "float": 0.5}))]);
+    }
+    
+    #[test]
+    fn keyword_value_of_plain_values() {
+        assert_eq!(keyword_value(&json!({"int": 10})).as_deref(), Some("10"));
+        assert_eq!(keyword_value(&json!({"float": 0.5})).as_deref(), Some("0.5"));
+        assert_eq!(keyword_value(&json!({"str": "dwindle"})).as_deref(), Some("dwindle"));
+        assert_eq!(keyword_value(&json!({"vec2": [0.0, 5.0]})).as_deref(), Some("0 5"));
+        assert_eq!(keyword_value(&json!({"set": false})), None);
+    }
+    
+    #[test]
+    fn keyword_value_of_custom_values() {
+        // Gradients get colour prefixes back
+        assert_eq!(
+            keyword_value(&json!({"custom": "ee33ccff ee00ff99 45deg"})).as_deref(),
+            Some("0xee33ccff 0xee00ff99 45deg")
+        );
+        assert_eq!(keyword_value(&json!({"custom": "ff444444"})).as_deref(), Some("0xff444444"));
+        assert_eq!(keyword_value(&json!({"custom": "44444444 0deg"})).as_deref(), Some("0x44444444 0deg"));
+        // Css gaps are separated by commas
+        assert_eq!(keyword_value(&json!({"custom": "5 10 5 10"})).as_deref(), Some("5,10,5,10"));
+        assert_eq!(keyword_value(&json!({"custom": "20"})).as_deref(), Some("20"));
+    }
+}
@@ -2,13 +2,17 @@ use gtk4::prelude::*;
 use gtk4::{Application, ApplicationWindow};
 use glib::clone;
 use regex::Regex;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
 use std::io::{self, BufRead};
 use std::path::{Path, PathBuf};
 use std::cell::RefCell;
 use std::rc::Rc;
 
+mod backup;
+mod ipc;
+mod schema;
+
 #[derive(Clone)]
 struct HyprVariable {
     name: String,
@@ -16,6 +20,9 @@ struct HyprVariable {
     file: String,
     line_number: usize,
     original_line: String,
+    /// Trailing `# comment` of the line including the whitespace before it,
+    /// kept when the line is rewritten
+    comment: String,
 }
 
 #[derive(Clone)]
@@ -63,15 +70,18 @@ fn resolve_source_path(source_path: &str, base_dir: &Path) -> Option<PathBuf> {
     None
 }
 
-fn parse_hyprland_configs(base_dir: &Path) -> (Vec<HyprVariable>, Vec<SourceStatement>) {
+/// Parse the config files, returning the variables, the source statements and
+/// the content hash of each file as it was read
+fn parse_hyprland_configs(base_dir: &Path) -> (Vec<HyprVariable>, Vec<SourceStatement>, HashMap<String, u64>) {
     let mut variables = Vec::new();
     let mut sources = Vec::new();
+    let mut file_hashes = HashMap::new();
     let mut processed_files = HashSet::new();
     
     // Start with the main config file
     let main_config = base_dir.join("hyprland.conf");
     if main_config.exists() {
-        process_config_file(&main_config, base_dir, &mut variables, &mut sources, &mut processed_files);
+        process_config_file(&main_config, base_dir, &mut variables, &mut sources, &mut file_hashes, &mut processed_files);
     } else {
         // If main config doesn't exist, try finding it in subdirectories
         if let Ok(entries) = fs::read_dir(base_dir) {
@@ -80,7 +90,7 @@ fn parse_hyprland_configs(base_dir: &Path) -> (Vec<HyprVariable>, Vec<SourceStat
                 if path.is_dir() {
                     let potential_config = path.join("hyprland.conf");
                     if potential_config.exists() {
-                        process_config_file(&potential_config, base_dir, &mut variables, &mut sources, &mut processed_files);
+                        process_config_file(&potential_config, base_dir, &mut variables, &mut sources, &mut file_hashes, &mut processed_files);
                         break;
                     }
                 }
@@ -93,20 +103,64 @@ fn parse_hyprland_configs(base_dir: &Path) -> (Vec<HyprVariable>, Vec<SourceStat
     while i < sources.len() {
         if let Some(resolved_path) = &sources[i].resolved_path.canonicalize().ok() {
             if !processed_files.contains(resolved_path) {
-                process_config_file(resolved_path, base_dir, &mut variables, &mut sources, &mut processed_files);
+                process_config_file(resolved_path, base_dir, &mut variables, &mut sources, &mut file_hashes, &mut processed_files);
             }
         }
         i += 1;
     }
     
-    (variables, sources)
+    (variables, sources, file_hashes)
 }
 
 fn normalize_variable_value(value: &str) -> String {
     value.trim().to_string()
 }
 
-fn process_config_file(path: &Path, base_dir: &Path, variables: &mut Vec<HyprVariable>, sources: &mut Vec<SourceStatement>, processed_files: &mut HashSet<PathBuf>) {
+/// Split a line into its code and its trailing comment, the way Hyprland reads
+/// it. `##` is an escaped `#` and is unescaped in the code. The comment keeps
+/// the whitespace before it so it can be written back unchanged.
+fn split_comment(line: &str) -> (String, &str) {
+    let mut code = String::with_capacity(line.len());
+    let mut chars = line.char_indices().peekable();
+    
+    while let Some((idx, c)) = chars.next() {
+        if c != '#' {
+            code.push(c);
+            continue;
+        }
+        if chars.next_if(|(_, next)| *next == '#').is_some() {
+            code.push('#');
+            continue;
+        }
+        
+        let comment_start = line[..idx].trim_end().len();
+        return (code.trim_end().to_string(), &line[comment_start..]);
+    }
+    
+    (code, "")
+}
+
+/// The line of a variable with a new value, keeping its indentation, the name
+/// as the line spells it and the trailing comment
+fn rewrite_variable_line(var: &HyprVariable, new_value: &str) -> String {
+    // Keep the name as the line spells it, inside a section or as a colon path
+    let var_name = var.original_line
+        .split('=')
+        .next()
+        .map(str::trim)
+        .unwrap_or(&var.name);
+    
+    // Get the original line to preserve formatting/indentation
+    let indentation = var.original_line
+        .chars()
+        .take_while(|c| c.is_whitespace())
+        .collect::<String>();
+    
+    // A `#` in the value has to be escaped so it does not start a comment
+    format!("{}{} = {}{}", indentation, var_name, new_value.replace('#', "##"), var.comment)
+}
+
+fn process_config_file(path: &Path, base_dir: &Path, variables: &mut Vec<HyprVariable>, sources: &mut Vec<SourceStatement>, file_hashes: &mut HashMap<String, u64>, processed_files: &mut HashSet<PathBuf>) {
     let canonical_path = match path.canonicalize() {
         Ok(p) => p,
         Err(_) => return,
@@ -119,16 +173,17 @@ fn process_config_file(path: &Path, base_dir: &Path, variables: &mut Vec<HyprVar
     
     processed_files.insert(canonical_path.clone());
     
-    let file = match fs::File::open(path) {
-        Ok(file) => file,
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
         Err(_) => return,
     };
     
-    // Regex for variable assignment: name = value
-    let var_regex = Regex::new(r"^\s*(\w+)\s*=\s*(.+?)\s*$").unwrap();
+    // Regex for variable assignment: name = value, where the name may be a
+    // colon path such as decoration:blur:size or contain dots like col.active_border
+    let var_regex = Regex::new(r"^\s*([\w.:-]+)\s*=\s*(.+?)\s*$").unwrap();
     
     // Regex for section headers
-    let section_regex = Regex::new(r"^\s*(\w+)\s*\{\s*$").unwrap();
+    let section_regex = Regex::new(r"^\s*([\w.:-]+)\s*\{\s*$").unwrap();
     
     // Regex for source statements
     let source_regex = Regex::new(r"^\s*source\s*=\s*(.+?)\s*$").unwrap();
@@ -136,18 +191,33 @@ fn process_config_file(path: &Path, base_dir: &Path, variables: &mut Vec<HyprVar
     let relative_path = path.strip_prefix(base_dir).unwrap_or(path);
     let file_display = relative_path.display().to_string();
     
-    let reader = io::BufReader::new(file);
-    let mut current_section = String::new();
+    // Remember what the file looked like so saving can refuse to overwrite newer edits
+    file_hashes.insert(file_display.clone(), backup::content_hash(&content));
+    
+    let reader = io::BufReader::new(content.as_bytes());
+    
+    // Sections can be nested, e.g. decoration { blur { ... } }
+    let mut sections: Vec<String> = Vec::new();
     
     for (line_number, line_result) in reader.lines().enumerate() {
         if let Ok(line) = line_result {
+            // Parse the code without its trailing comment
+            let (code, comment) = split_comment(&line);
+            
+            // Each `}` leaves the innermost section, and text may follow it on the same line
+            let mut code = code.trim_start();
+            while let Some(rest) = code.strip_prefix('}') {
+                sections.pop();
+                code = rest.trim_start();
+            }
+            
             // Ignore comments and empty lines so we only parse meaningful configuration lines
-            if line.trim().starts_with('#') || line.trim().is_empty() {
+            if code.trim().is_empty() {
                 continue;
             }
             
             // Handle source statements
-            if let Some(cap) = source_regex.captures(&line) {
+            if let Some(cap) = source_regex.captures(code) {
                 let source_path_str = cap[1].to_string();
                 let resolved_path = resolve_source_path(&source_path_str, base_dir)
                     .unwrap_or_else(|| PathBuf::from(&source_path_str));
@@ -161,26 +231,21 @@ fn process_config_file(path: &Path, base_dir: &Path, variables: &mut Vec<HyprVar
             }
             
             // Handle section headers
-            if let Some(cap) = section_regex.captures(&line) {
-                current_section = cap[1].to_string();
-                continue;
-            }
-            
-            // Handle section closing
-            if line.trim() == "}" {
-                current_section.clear();
+            if let Some(cap) = section_regex.captures(code) {
+                sections.push(cap[1].to_string());
                 continue;
             }
             
             // Handle variable assignments
-            if let Some(cap) = var_regex.captures(&line) {
+            if let Some(cap) = var_regex.captures(code) {
                 let var_name = cap[1].to_string();
                 let var_value = normalize_variable_value(&cap[2]);
                 
-                let full_name = if current_section.is_empty() {
+                // Both spellings give the canonical section:option name
+                let full_name = if sections.is_empty() {
                     var_name
                 } else {
-                    format!("{}.{}", current_section, var_name)
+                    format!("{}:{}", sections.join(":"), var_name)
                 };
                 
                 variables.push(HyprVariable {
@@ -189,15 +254,21 @@ fn process_config_file(path: &Path, base_dir: &Path, variables: &mut Vec<HyprVar
                     file: file_display.clone(),
                     line_number: line_number + 1,
                     original_line: line.clone(),
+                    comment: comment.to_string(),
                 });
             }
         }
     }
 }
 
-fn save_changes(changes: &[(String, usize, String, String)], base_dir: &Path) -> Result<(), String> {
-    // Group changes by file
-    let mut file_changes: HashMap<String, Vec<(usize, String, String)>> = HashMap::new();
+/// Write the changes to the config files. Every file must still have the
+/// content it had when it was loaded and every changed line must still read
+/// as expected, otherwise nothing is written. The files are backed up into a
+/// snapshot first, which is returned. If a file cannot be written, the files
+/// already written are put back. `file_hashes` is updated to the new content.
+fn save_changes(changes: &[(String, usize, String, String)], base_dir: &Path, file_hashes: &mut HashMap<String, u64>) -> Result<backup::Snapshot, String> {
+    // Group changes by file, written in path order
+    let mut file_changes: BTreeMap<String, Vec<(usize, String, String)>> = BTreeMap::new();
     
     for (file, line_number, new_value, original_line) in changes {
         file_changes.entry(file.clone())
@@ -205,7 +276,8 @@ fn save_changes(changes: &[(String, usize, String, String)], base_dir: &Path) ->
             .push((*line_number, new_value.clone(), original_line.clone()));
     }
     
-    // Process each file
+    // Build the new content of every file before writing any of them
+    let mut new_contents: Vec<(String, String, String)> = Vec::new();
     for (file_path, changes) in file_changes {
         let full_path = base_dir.join(&file_path);
         
@@ -215,57 +287,75 @@ fn save_changes(changes: &[(String, usize, String, String)], base_dir: &Path) ->
             Err(e) => return Err(format!("Failed to read {}: {}", file_path, e)),
         };
         
-        let lines: Vec<String> = file_content.lines().map(String::from).collect();
-        let mut new_lines = lines.clone();
+        // Refuse to save over edits made since the file was loaded
+        if file_hashes.get(&file_path) != Some(&backup::content_hash(&file_content)) {
+            return Err(format!(
+                "{} was changed by another program since it was loaded. Reopen Hyprland Settings to pick up the changes before saving.",
+                file_path
+            ));
+        }
+        
+        // Keep each line's own line ending, including a missing one at the end
+        let mut lines: Vec<(String, &str)> = file_content
+            .split_inclusive('\n')
+            .map(|line| {
+                let text = line.trim_end_matches(['\n', '\r']);
+                (text.to_string(), &line[text.len()..])
+            })
+            .collect();
         
         // Apply changes
         for (line_number, new_value, original_line) in changes {
-            if line_number > 0 && line_number <= new_lines.len() {
-                let line_idx = line_number - 1;
-                let original_line_in_file = &lines[line_idx];
-                
-                // More flexible line matching - ignore whitespace differences
-                let original_line_trimmed = original_line.trim();
-                let file_line_trimmed = original_line_in_file.trim();
-                
-                // Extract just the variable part for safer matching
-                let original_var_part = if let Some(idx) = original_line_trimmed.find('=') {
-                    original_line_trimmed[0..idx].trim()
-                } else {
-                    original_line_trimmed
-                };
-                
-                let file_var_part = if let Some(idx) = file_line_trimmed.find('=') {
-                    file_line_trimmed[0..idx].trim()
-                } else {
-                    file_line_trimmed
-                };
-                
-                // Check if the line contains the right variable, even if the value has changed
-                if file_var_part == original_var_part || 
-                   file_line_trimmed.contains(original_var_part) || 
-                   original_line_trimmed.contains(file_var_part) {
-                    new_lines[line_idx] = new_value.clone();
-                } else {
-                    return Err(format!(
-                        "Line mismatch in {} at line {}:\n- Expected: '{}'\n- Found: '{}'\n\nVariable parts:\n- Expected: '{}'\n- Found: '{}'",
-                        file_path, line_number, original_line, original_line_in_file, original_var_part, file_var_part
-                    ));
+            let file_line_count = lines.len();
+            let line = match line_number.checked_sub(1).and_then(|idx| lines.get_mut(idx)) {
+                Some((text, _)) => text,
+                None => {
+                    return Err(format!("Invalid line number {} in {} (file has {} lines)",
+                                      line_number, file_path, file_line_count));
                 }
-            } else {
-                return Err(format!("Invalid line number {} in {} (file has {} lines)", 
-                                  line_number, file_path, new_lines.len()));
+            };
+            
+            // Only replace the exact line that was loaded
+            if *line != original_line {
+                return Err(format!(
+                    "Line mismatch in {} at line {}:\n- Expected: '{}'\n- Found: '{}'",
+                    file_path, line_number, original_line, line
+                ));
             }
+            *line = new_value;
         }
         
-        // Write the file
-        match fs::write(&full_path, new_lines.join("\n")) {
-            Ok(_) => (),
-            Err(e) => return Err(format!("Failed to write {}: {}", file_path, e)),
+        let new_content: String = lines.iter().map(|(text, ending)| format!("{}{}", text, ending)).collect();
+        new_contents.push((file_path, file_content, new_content));
+    }
+    
+    // Back up the files, then replace them
+    let files: Vec<String> = new_contents.iter().map(|(file_path, _, _)| file_path.clone()).collect();
+    let snapshot = backup::create_snapshot(base_dir, &files)?;
+    
+    for (written, (file_path, _, new_content)) in new_contents.iter().enumerate() {
+        if let Err(err) = backup::write_atomically(&base_dir.join(file_path), new_content) {
+            // Put back the files written so far so the config stays consistent
+            let failed: Vec<String> = new_contents[..written]
+                .iter()
+                .filter(|(file_path, old_content, _)| backup::write_atomically(&base_dir.join(file_path), old_content).is_err())
+                .map(|(file_path, _, _)| file_path.clone())
+                .collect();
+            if failed.is_empty() {
+                return Err(format!("{}\nNo file was changed.", err));
+            }
+            return Err(format!(
+                "{}\nCould not undo the changes to {}, restore the backup {} from the history.",
+                err, failed.join(", "), snapshot.id
+            ));
         }
     }
     
-    Ok(())
+    for (file_path, _, new_content) in new_contents {
+        file_hashes.insert(file_path, backup::content_hash(&new_content));
+    }
+    
+    Ok(snapshot)
 }
 
 fn show_warning_dialog(parent: &ApplicationWindow, message: &str, on_ok: Box<dyn Fn() + 'static>) {
@@ -550,6 +640,15 @@ fn build_ui(application: &Application) {
         .destructive-action:hover {
             background-color: #f5c2e7;  /* Lighter red on hover */
         }
+        .default-marker {
+            color: #f9e2af;  /* Catppuccin yellow */
+            font-size: smaller;
+        }
+        .drift-marker {
+            color: #fab387;  /* Catppuccin peach */
+            font-size: smaller;
+            font-weight: bold;
+        }
         "
     );
     gtk4::style_context_add_provider_for_display(
@@ -578,11 +677,359 @@ fn build_ui(application: &Application) {
     
     // Use a one-shot idle handler to load the content
     glib::idle_add_local_once(clone!(@weak window => move || {
-        load_application_content(&window);
+        load_application_content(&window, true);
     }));
 }
 
-fn load_application_content(window: &ApplicationWindow) {
+/// Show the backups taken before each save, newest first, with a button to restore each one
+fn show_history_window(parent: &ApplicationWindow, config_dir: &Path, live_preview: &Rc<RefCell<Option<ipc::LivePreview>>>) {
+    let history_window = gtk4::Window::builder()
+        .title("Backup History")
+        .transient_for(parent)
+        .modal(true)
+        .default_width(560)
+        .default_height(420)
+        .build();
+    
+    let scrolled_window = gtk4::ScrolledWindow::new();
+    scrolled_window.set_vexpand(true);
+    
+    let list_box = gtk4::ListBox::new();
+    list_box.set_selection_mode(gtk4::SelectionMode::None);
+    list_box.set_margin_top(12);
+    list_box.set_margin_bottom(12);
+    list_box.set_margin_start(12);
+    list_box.set_margin_end(12);
+    
+    let snapshots = backup::list_snapshots();
+    if snapshots.is_empty() {
+        let empty_label = gtk4::Label::new(Some("No backups yet. A backup is taken every time you save."));
+        empty_label.add_css_class("dim-label");
+        empty_label.set_margin_top(24);
+        list_box.append(&empty_label);
+    }
+    
+    for snapshot in snapshots {
+        let row = gtk4::Box::new(gtk4::Orientation::Horizontal, 12);
+        row.set_margin_top(6);
+        row.set_margin_bottom(6);
+        
+        let text_box = gtk4::Box::new(gtk4::Orientation::Vertical, 2);
+        text_box.set_hexpand(true);
+        
+        let id_label = gtk4::Label::new(Some(&snapshot.id));
+        id_label.set_xalign(0.0);
+        text_box.append(&id_label);
+        
+        let files: Vec<String> = snapshot.files.iter().map(|(_, file)| file.clone()).collect();
+        let files_label = gtk4::Label::new(Some(&files.join(", ")));
+        files_label.add_css_class("dim-label");
+        files_label.set_xalign(0.0);
+        files_label.set_ellipsize(gtk4::pango::EllipsizeMode::End);
+        files_label.set_tooltip_text(Some(&files.join("\n")));
+        text_box.append(&files_label);
+        
+        let restore_button = gtk4::Button::with_label("Restore");
+        restore_button.set_valign(gtk4::Align::Center);
+        
+        let parent_clone = parent.clone();
+        let history_window_clone = history_window.clone();
+        let config_dir_clone = config_dir.to_path_buf();
+        let live_preview_clone = live_preview.clone();
+        restore_button.connect_clicked(move |_| {
+            let parent_clone2 = parent_clone.clone();
+            let history_window_clone2 = history_window_clone.clone();
+            let config_dir_clone2 = config_dir_clone.clone();
+            let live_preview_clone2 = live_preview_clone.clone();
+            let snapshot_path = snapshot.path.clone();
+            let message = format!(
+                "Restore {} from the backup taken at {}? Unsaved changes are lost. The current files are backed up first.",
+                files.join(", "),
+                snapshot.id
+            );
+            
+            show_warning_dialog(&parent_clone, &message, Box::new(move || {
+                // The snapshot may have been listed before another restore, so look it up again
+                let snapshot = backup::list_snapshots().into_iter().find(|snapshot| snapshot.path == snapshot_path);
+                let result = match snapshot {
+                    Some(snapshot) => backup::restore_snapshot(&snapshot, &config_dir_clone2),
+                    None => Err("The backup no longer exists".to_string()),
+                };
+                
+                history_window_clone2.close();
+                match result {
+                    Ok(backup) => {
+                        // Previewed values are unsaved changes too, undo them in Hyprland
+                        // before the reload drops the preview
+                        let reverted = match live_preview_clone2.borrow_mut().as_mut() {
+                            Some(preview) => preview.revert(),
+                            None => Ok(0),
+                        };
+                        
+                        // Reload so the editor shows the restored files
+                        load_application_content(&parent_clone2, false);
+                        match reverted {
+                            Ok(_) => show_success_dialog(
+                                &parent_clone2,
+                                &format!("Restored the backup. The files it replaced were backed up as {}.", backup.id),
+                                Box::new(|| {})
+                            ),
+                            Err(err) => show_error_dialog(
+                                &parent_clone2,
+                                &format!("Restored the backup, but failed to undo some previewed options in Hyprland:\n{}", err)
+                            ),
+                        }
+                    }
+                    Err(err) => show_error_dialog(&parent_clone2, &format!("Failed to restore the backup: {}", err)),
+                }
+            }));
+        });
+        
+        row.append(&text_box);
+        row.append(&restore_button);
+        list_box.append(&row);
+    }
+    
+    scrolled_window.set_child(Some(&list_box));
+    history_window.set_child(Some(&scrolled_window));
+    history_window.present();
+}
+
+/// Push a value to the running compositor and report the result in the status label
+fn preview_value(live_preview: &RefCell<Option<ipc::LivePreview>>, option: &str, value: &str, status: &gtk4::Label, revert_button: &gtk4::Button) {
+    let mut live_preview = live_preview.borrow_mut();
+    let Some(preview) = live_preview.as_mut() else {
+        return;
+    };
+    
+    match preview.apply(option, value) {
+        Ok(()) => status.set_text(&format!("Previewing {} = {}", option, value)),
+        Err(err) => status.set_text(&err),
+    }
+    revert_button.set_sensitive(preview.has_changes());
+}
+
+/// The type of the typed editor for a value, `None` when it is edited as text.
+/// `value` is the value without its trailing comment.
+fn typed_editor_kind(value: &str, option: Option<&schema::OptionSchema>) -> Option<schema::OptionType> {
+    let value = value.trim();
+    
+    // Values that use variables can only be edited as text
+    let kind = option.filter(|_| !value.contains('$'))?.kind;
+    let typed = match kind {
+        schema::OptionType::Bool => schema::parse_bool(value).is_some(),
+        schema::OptionType::Int { min, max } => value.parse::<i64>().is_ok_and(|number| (min..=max).contains(&number)),
+        schema::OptionType::Float { min, max } => value.parse::<f64>().is_ok_and(|number| (min..=max).contains(&number)),
+        schema::OptionType::Enum(values) => values.contains(&value),
+        schema::OptionType::Color => schema::parse_color(value).is_some(),
+        _ => false,
+    };
+    typed.then_some(kind)
+}
+
+/// Create the widget that edits a value: a switch, spin button, dropdown or
+/// colour button when the schema knows the option, otherwise a plain entry.
+/// `value` must not contain the trailing comment of the line.
+/// `on_change` receives the new value as it should be written to the config.
+#[allow(deprecated)]
+fn create_value_editor<F: Fn(String) + 'static>(value: &str, option: Option<&'static schema::OptionSchema>, on_change: F) -> gtk4::Widget {
+    let value = value.trim();
+    
+    match typed_editor_kind(value, option) {
+        Some(schema::OptionType::Bool) => {
+            // Keep the spelling the config already uses
+            let (on, off) = match value.to_lowercase().as_str() {
+                "yes" | "no" => ("yes", "no"),
+                "on" | "off" => ("on", "off"),
+                "1" | "0" => ("1", "0"),
+                _ => ("true", "false"),
+            };
+            
+            let switch = gtk4::Switch::new();
+            switch.set_active(schema::parse_bool(value) == Some(true));
+            switch.set_halign(gtk4::Align::Start);
+            switch.set_valign(gtk4::Align::Center);
+            switch.set_hexpand(true);
+            switch.connect_active_notify(move |switch| {
+                on_change(if switch.is_active() { on } else { off }.to_string());
+            });
+            return switch.upcast();
+        }
+        Some(schema::OptionType::Int { min, max }) => {
+            let spin = gtk4::SpinButton::with_range(min as f64, max as f64, 1.0);
+            spin.set_value(value.parse::<f64>().unwrap_or_default());
+            spin.set_halign(gtk4::Align::Start);
+            spin.set_hexpand(true);
+            spin.connect_value_changed(move |spin| {
+                on_change(spin.value_as_int().to_string());
+            });
+            return spin.upcast();
+        }
+        Some(schema::OptionType::Float { min, max }) => {
+            let spin = gtk4::SpinButton::with_range(min, max, 0.05);
+            spin.set_digits(value.split_once('.').map(|(_, decimals)| decimals.len().clamp(2, 4) as u32).unwrap_or(2));
+            spin.set_value(value.parse().unwrap_or_default());
+            spin.set_halign(gtk4::Align::Start);
+            spin.set_hexpand(true);
+            spin.connect_value_changed(move |spin| {
+                // Drop the trailing zeros the spin button pads with
+                let text = format!("{:.*}", spin.digits() as usize, spin.value());
+                let text = text.trim_end_matches('0');
+                on_change(format!("{}{}", text, if text.ends_with('.') { "0" } else { "" }));
+            });
+            return spin.upcast();
+        }
+        Some(schema::OptionType::Enum(values)) => {
+            let dropdown = gtk4::DropDown::from_strings(values);
+            dropdown.set_selected(values.iter().position(|v| *v == value).unwrap_or_default() as u32);
+            dropdown.set_halign(gtk4::Align::Start);
+            dropdown.set_hexpand(true);
+            dropdown.connect_selected_notify(move |dropdown| {
+                if let Some(selected) = values.get(dropdown.selected() as usize) {
+                    on_change(selected.to_string());
+                }
+            });
+            return dropdown.upcast();
+        }
+        Some(schema::OptionType::Color) => {
+            let [r, g, b, a] = schema::parse_color(value).unwrap_or_default();
+            let rgba = gtk4::gdk::RGBA::new(r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0, a as f32 / 255.0);
+            let color_button = gtk4::ColorButton::with_rgba(&rgba);
+            color_button.set_use_alpha(true);
+            color_button.set_tooltip_text(Some(value));
+            color_button.set_halign(gtk4::Align::Start);
+            color_button.set_hexpand(true);
+            color_button.connect_color_set(move |button| {
+                let rgba = button.rgba();
+                let channel = |c: f32| (c * 255.0).round() as u8;
+                let text = schema::format_color([channel(rgba.red()), channel(rgba.green()), channel(rgba.blue()), channel(rgba.alpha())]);
+                button.set_tooltip_text(Some(&text));
+                on_change(text);
+            });
+            return color_button.upcast();
+        }
+        _ => {}
+    }
+    
+    // Fall back to an entry, flagging values the schema rejects unless they use variables
+    let option = option.filter(|_| !value.contains('$'));
+    let entry = gtk4::Entry::new();
+    entry.set_text(value);
+    entry.set_halign(gtk4::Align::Start);
+    entry.set_hexpand(true);
+    entry.connect_changed(move |entry| {
+        let text = entry.text().to_string();
+        match option.map(|option| option.validate(&text)) {
+            Some(Err(err)) => {
+                entry.add_css_class("error");
+                entry.set_tooltip_text(Some(&err));
+            }
+            _ => {
+                entry.remove_css_class("error");
+                entry.set_tooltip_text(None);
+            }
+        }
+        on_change(text);
+    });
+    entry.upcast()
+}
+
+/// Row filters offered next to the search box, with the css class a row needs to be shown
+const ROW_FILTERS: &[(&str, Option<&str>)] = &[
+    ("All variables", None),
+    ("Drifted from Hyprland", Some("drifted")),
+    ("At default value", Some("at-default")),
+    ("Set in multiple files", Some("multi-file")),
+];
+
+/// Show the rows that contain the search query and pass the selected filter
+fn filter_rows(list_boxes: &HashMap<String, gtk4::ListBox>, query: &str, filter: u32) {
+    let required_class = ROW_FILTERS.get(filter as usize).and_then(|(_, class)| *class);
+    
+    for list_box in list_boxes.values() {
+        // In GTK4, we need to get the rows differently
+        for row in list_box.observe_children().snapshot().iter() {
+            if let Some(row) = row.downcast_ref::<gtk4::ListBoxRow>() {
+                if let Some(hbox) = row.child() {
+                    let mut contains_query = false;
+                    
+                    // Check if the row contains the text in either column
+                    if let Some(box_container) = hbox.downcast_ref::<gtk4::Box>() {
+                        // In GTK4, we iterate through children differently
+                        let mut first_child = box_container.first_child();
+                        while let Some(child) = first_child {
+                            // Check if it's a label (variable name)
+                            if child.has_css_class("default-marker") || child.has_css_class("drift-marker") {
+                                // Not part of the variable
+                            } else if let Some(label) = child.downcast_ref::<gtk4::Label>() {
+                                let text = label.text().to_string().to_lowercase();
+                                if text.contains(query) {
+                                    contains_query = true;
+                                    break;
+                                }
+                            }
+                            
+                            first_child = child.next_sibling();
+                        }
+                    }
+                    
+                    // The value is kept in the row's name, whatever widget edits it
+                    if row.widget_name().to_lowercase().contains(query) {
+                        contains_query = true;
+                    }
+                    
+                    let passes_filter = match required_class {
+                        Some(class) => row.has_css_class(class),
+                        None => true,
+                    };
+                    row.set_visible((query.is_empty() || contains_query) && passes_filter);
+                }
+            }
+        }
+    }
+}
+
+/// Compare the value Hyprland is running with the one in the file for each
+/// effective definition, flagging the rows that differ. Returns how many differ.
+fn check_drift(ipc: &ipc::HyprlandIpc, variables: &[HyprVariable], drift_rows: &[(usize, gtk4::ListBoxRow, gtk4::Label)]) -> Result<usize, String> {
+    // Values with variables cannot be compared, the others are read in one go
+    let compared: Vec<&(usize, gtk4::ListBoxRow, gtk4::Label)> = drift_rows
+        .iter()
+        .filter(|(idx, _, _)| !variables[*idx].value.contains('$'))
+        .collect();
+    let names: Vec<&str> = compared.iter().map(|(idx, _, _)| variables[*idx].name.as_str()).collect();
+    let runtime_values = ipc.options_json(&names)?;
+    
+    let mut drifted = 0;
+    for (idx, row, marker) in drift_rows {
+        let var = &variables[*idx];
+        let runtime = compared
+            .iter()
+            .position(|(compared_idx, _, _)| compared_idx == idx)
+            .and_then(|position| runtime_values[position].as_ref())
+            .and_then(ipc::keyword_value);
+        
+        let kind = schema::lookup(&var.name).map(|option| option.kind);
+        let is_drifted = runtime
+            .as_ref()
+            .is_some_and(|runtime| !schema::same_value(kind, runtime, &var.value));
+        
+        marker.set_visible(is_drifted);
+        if is_drifted {
+            marker.set_tooltip_text(runtime.map(|runtime| format!("Hyprland is running {}", runtime)).as_deref());
+            row.add_css_class("drifted");
+            drifted += 1;
+        } else {
+            row.remove_css_class("drifted");
+        }
+    }
+    
+    Ok(drifted)
+}
+
+/// Build the editor from the config files. Called again after restoring a
+/// backup, in which case `first_load` is false.
+fn load_application_content(window: &ApplicationWindow, first_load: bool) {
     // Find Hyprland config directory
     let config_dir = match find_hyprland_config_dir() {
         Some(dir) => dir,
@@ -593,15 +1040,17 @@ fn load_application_content(window: &ApplicationWindow) {
     };
     
     // Parse variables from config files
-    let (variables, sources) = parse_hyprland_configs(&config_dir);
+    let (variables, sources, file_hashes) = parse_hyprland_configs(&config_dir);
     
     if variables.is_empty() && sources.is_empty() {
         show_error_window(window, "No variables or sources found in Hyprland configuration files.");
         return;
     }
     
-    // Show initial warning
-    show_initial_warning(window);
+    // Show initial warning, once per session
+    if first_load {
+        show_initial_warning(window);
+    }
     
     // Create a main box
     let main_box = gtk4::Box::new(gtk4::Orientation::Vertical, 0);
@@ -665,6 +1114,19 @@ fn load_application_content(window: &ApplicationWindow) {
     search_entry.set_hexpand(true);
     search_box.append(&search_entry);
     
+    let filter_names: Vec<&str> = ROW_FILTERS.iter().map(|(name, _)| *name).collect();
+    let filter_dropdown = gtk4::DropDown::from_strings(&filter_names);
+    search_box.append(&filter_dropdown);
+    
+    // Compare the running compositor with the files
+    let drift_label = gtk4::Label::new(None);
+    drift_label.add_css_class("dim-label");
+    search_box.append(&drift_label);
+    
+    let check_button = gtk4::Button::with_label("Check Hyprland");
+    check_button.set_tooltip_text(Some("Compare the values Hyprland is running with the ones in the files"));
+    search_box.append(&check_button);
+    
     main_box.append(&search_box);
     main_box.append(&gtk4::Separator::new(gtk4::Orientation::Horizontal));
     
@@ -687,7 +1149,17 @@ fn load_application_content(window: &ApplicationWindow) {
     
     // Shared state to track changes
     let changes = Rc::new(RefCell::new(Vec::new()));
+    let invalid: Rc<RefCell<HashMap<(String, usize), String>>> = Rc::new(RefCell::new(HashMap::new()));
+    
+    // Live preview pushes edited values to the running compositor
+    let live_preview: Rc<RefCell<Option<ipc::LivePreview>>> = Rc::new(RefCell::new(None));
+    let edited_values: Rc<RefCell<Vec<(String, String)>>> = Rc::new(RefCell::new(Vec::new()));
+    let preview_button = gtk4::ToggleButton::with_label("Preview");
+    let revert_button = gtk4::Button::with_label("Revert");
+    revert_button.set_sensitive(false);
+    let preview_status = gtk4::Label::new(None);
     let variables = Rc::new(RefCell::new(variables));
+    let file_hashes = Rc::new(RefCell::new(file_hashes));
     
     // First add a dedicated tab for source statements if there are any
     if !sources.is_empty() {
@@ -706,6 +1178,7 @@ fn load_application_content(window: &ApplicationWindow) {
         for chunk in sources.chunks(batch_size) {
             for source in chunk {
                 let row = gtk4::ListBoxRow::new();
+                row.set_widget_name(&source.path);
                 let hbox = gtk4::Box::new(gtk4::Orientation::Horizontal, 12);
                 hbox.set_margin_top(6);
                 hbox.set_margin_bottom(6);
@@ -754,6 +1227,18 @@ fn load_application_content(window: &ApplicationWindow) {
         notebook.insert_page(&scrolled_window, Some(&label), Some(0));
     }
     
+    // Find the files that set each option and the definition that takes effect.
+    // Keywords like `bind` add to each other instead, so they are left out.
+    let mut option_files: HashMap<String, HashSet<String>> = HashMap::new();
+    let mut effective_definitions: HashMap<String, usize> = HashMap::new();
+    for (idx, var) in variables.borrow().iter().enumerate().filter(|(_, var)| schema::is_option(&var.name)) {
+        option_files.entry(var.name.clone()).or_default().insert(var.file.clone());
+        effective_definitions.insert(var.name.clone(), idx);
+    }
+    
+    // Rows of the effective definitions, checked against the runtime values
+    let drift_rows: Rc<RefCell<Vec<(usize, gtk4::ListBoxRow, gtk4::Label)>>> = Rc::new(RefCell::new(Vec::new()));
+    
     // Sort file names for consistent tab order
     let mut file_names: Vec<String> = file_vars.keys().cloned().collect();
     file_names.sort();
@@ -779,6 +1264,8 @@ fn load_application_content(window: &ApplicationWindow) {
                 let var = &variables.borrow()[idx];
                 
                 let row = gtk4::ListBoxRow::new();
+                // Searching matches the current value through the row's name
+                row.set_widget_name(&var.value);
                 let hbox = gtk4::Box::new(gtk4::Orientation::Horizontal, 12);
                 hbox.set_margin_top(6);
                 hbox.set_margin_bottom(6);
@@ -791,18 +1278,97 @@ fn load_application_content(window: &ApplicationWindow) {
                 key_label.set_xalign(0.0);
                 key_label.set_selectable(true);
                 
-                // Create an editable entry for the value instead of a label
-                let value_entry = gtk4::Entry::new();
-                value_entry.set_text(&var.value);
-                value_entry.set_halign(gtk4::Align::Start);
-                value_entry.set_hexpand(true);
+                // Describe the option when the schema knows it
+                let option = schema::lookup(&var.name);
+                if let Some(option) = option {
+                    key_label.set_tooltip_text(Some(&format!("{}\nDefault: {}", option.description, option.default)));
+                }
+                
+                // Mark values that differ from the default
+                let default_marker = gtk4::Label::new(Some("differs from default"));
+                default_marker.add_css_class("default-marker");
+                default_marker.set_visible(option.is_some_and(|option| !option.is_default(&var.value)));
+                if let Some(option) = option {
+                    default_marker.set_tooltip_text(Some(&format!("Default: {}", option.default)));
+                }
+                if option.is_some_and(|option| option.is_default(&var.value)) {
+                    row.add_css_class("at-default");
+                }
+                
+                // Mark options that are set in more than one file
+                let files = option_files.get(&var.name).cloned().unwrap_or_default();
+                let override_marker = gtk4::Label::new(Some(&format!("set in {} files", files.len())));
+                override_marker.add_css_class("default-marker");
+                override_marker.set_visible(files.len() > 1);
+                if files.len() > 1 {
+                    let mut files: Vec<&String> = files.iter().collect();
+                    files.sort();
+                    let last = &variables.borrow()[effective_definitions[&var.name]];
+                    override_marker.set_tooltip_text(Some(&format!(
+                        "Also set in {}\nThe value at {}:{} takes effect",
+                        files.iter().filter(|file| ***file != var.file).map(|file| file.as_str()).collect::<Vec<_>>().join(", "),
+                        last.file,
+                        last.line_number
+                    )));
+                    row.add_css_class("multi-file");
+                }
+                
+                // Flag the effective definition when Hyprland runs a different value
+                let drift_marker = gtk4::Label::new(Some("differs from Hyprland"));
+                drift_marker.add_css_class("drift-marker");
+                drift_marker.set_visible(false);
+                if effective_definitions.get(&var.name) == Some(&idx) {
+                    drift_rows.borrow_mut().push((idx, row.clone(), drift_marker.clone()));
+                }
                 
-                // Connect to the changed signal
                 let changes_clone = changes.clone();
+                let invalid_clone = invalid.clone();
+                let default_marker_clone = default_marker.clone();
+                let row_clone = row.clone();
+                let live_preview_clone = live_preview.clone();
+                let edited_values_clone = edited_values.clone();
+                let preview_button_clone = preview_button.clone();
+                let revert_button_clone = revert_button.clone();
+                let preview_status_clone = preview_status.clone();
+                let variables_clone = variables.clone();
                 let var_clone = var.clone();
                 
-                value_entry.connect_changed(move |entry| {
-                    let new_text = entry.text().to_string();
+                let value_editor = create_value_editor(&var.value, option, move |new_text| {
+                    // Remember values the schema rejects so saving can refuse them
+                    let key = (var_clone.file.clone(), var_clone.line_number);
+                    match option.map(|option| option.validate(&new_text)) {
+                        Some(Err(err)) if !new_text.contains('$') => {
+                            invalid_clone.borrow_mut().insert(key.clone(), format!("{} = {}: {}", var_clone.name, new_text.trim(), err));
+                        }
+                        _ => {
+                            invalid_clone.borrow_mut().remove(&key);
+                        }
+                    }
+                    row_clone.set_widget_name(new_text.trim());
+                    default_marker_clone.set_visible(option.is_some_and(|option| !option.is_default(&new_text)));
+                    if option.is_some_and(|option| option.is_default(&new_text)) {
+                        row_clone.add_css_class("at-default");
+                    } else {
+                        row_clone.remove_css_class("at-default");
+                    }
+                    
+                    // Keep the latest valid value of each option for the live preview
+                    let option_name = var_clone.name.clone();
+                    let previewable = !option_name.starts_with('$')
+                        && !new_text.contains('$')
+                        && !invalid_clone.borrow().contains_key(&key);
+                    edited_values_clone.borrow_mut().retain(|(name, _)| *name != option_name);
+                    if previewable {
+                        edited_values_clone.borrow_mut().push((option_name.clone(), new_text.trim().to_string()));
+                    }
+                    
+                    if preview_button_clone.is_active() {
+                        if previewable {
+                            preview_value(&live_preview_clone, &option_name, new_text.trim(), &preview_status_clone, &revert_button_clone);
+                        } else {
+                            preview_status_clone.set_text(&format!("Not previewing {}, the value is invalid or uses a variable", option_name));
+                        }
+                    }
                     
                     // Always add or update the change
                     let mut changes_ref = changes_clone.borrow_mut();
@@ -812,38 +1378,24 @@ fn load_application_content(window: &ApplicationWindow) {
                         *f != var_clone.file || *ln != var_clone.line_number
                     });
                     
-                    // Extract the actual variable name from section.varname
-                    let var_name = if var_clone.name.contains('.') {
-                        let parts: Vec<&str> = var_clone.name.split('.').collect();
-                        if let Some(last_part) = parts.last() {
-                            last_part.to_string()
-                        } else {
-                            var_clone.name.clone()
-                        }
-                    } else {
-                        var_clone.name.clone()
-                    };
-                    
-                    // Get the original line to preserve formatting/indentation
-                    let indentation = var_clone.original_line
-                        .chars()
-                        .take_while(|c| c.is_whitespace())
-                        .collect::<String>();
-                    
-                    // Include proper spacing in the formatted line
-                    let new_line = format!("{}{} = {}", indentation, var_name, new_text);
+                    // The line as it is in the file now, which changes after each save
+                    let original_line = variables_clone.borrow()[idx].original_line.clone();
+                    let new_line = rewrite_variable_line(&variables_clone.borrow()[idx], &new_text);
                     
                     // Always add the change regardless of comparison with original value
                     changes_ref.push((
                         var_clone.file.clone(),
                         var_clone.line_number,
                         new_line,
-                        var_clone.original_line.clone(),
+                        original_line,
                     ));
                 });
                 
                 hbox.append(&key_label);
-                hbox.append(&value_entry);
+                hbox.append(&value_editor);
+                hbox.append(&default_marker);
+                hbox.append(&override_marker);
+                hbox.append(&drift_marker);
                 row.set_child(Some(&hbox));
                 list_box.append(&row);
             }
@@ -876,6 +1428,32 @@ fn load_application_content(window: &ApplicationWindow) {
     // Add the notebook box to the main box
     main_box.append(&notebook_box);
     
+    // Query the runtime value of every effective definition and refresh the filters
+    let check_runtime: Rc<dyn Fn()> = {
+        let variables = variables.clone();
+        let drift_rows = drift_rows.clone();
+        let drift_label = drift_label.clone();
+        let list_boxes = list_boxes.clone();
+        let search_entry = search_entry.clone();
+        let filter_dropdown = filter_dropdown.clone();
+        Rc::new(move || {
+            match ipc::HyprlandIpc::from_env() {
+                Ok(ipc) => match check_drift(&ipc, &variables.borrow(), &drift_rows.borrow()) {
+                    Ok(drifted) => drift_label.set_text(&format!("{} differ from Hyprland", drifted)),
+                    Err(err) => drift_label.set_text(&err),
+                },
+                Err(_) => drift_label.set_text("Hyprland is not running"),
+            }
+            filter_rows(&list_boxes, &search_entry.text().to_lowercase(), filter_dropdown.selected());
+        })
+    };
+    
+    let check_runtime_clone = check_runtime.clone();
+    check_button.connect_clicked(move |_| check_runtime_clone());
+    
+    let check_runtime_clone = check_runtime.clone();
+    glib::idle_add_local_once(move || check_runtime_clone());
+    
     // Create a bottom box for the Save Button
     let bottom_box = gtk4::Box::new(gtk4::Orientation::Horizontal, 0);
     bottom_box.set_margin_top(16);
@@ -896,16 +1474,126 @@ fn load_application_content(window: &ApplicationWindow) {
     save_button.set_height_request(42); // Make button taller
     save_button.set_width_request(160); // Set a wider fixed width
     
+    // Preview and revert sit next to the save button
+    for button in [preview_button.upcast_ref::<gtk4::Widget>(), revert_button.upcast_ref()] {
+        button.add_css_class("pill");
+        button.set_margin_top(8);
+        button.set_margin_bottom(8);
+        button.set_margin_start(8);
+        button.set_margin_end(8);
+        button.set_height_request(42);
+        button.set_width_request(120);
+    }
+    preview_button.set_tooltip_text(Some("Apply edits to the running Hyprland instance as you make them"));
+    revert_button.set_tooltip_text(Some("Restore the values Hyprland had before previewing"));
+    
+    // History lists the backups taken before each save
+    let history_button = gtk4::Button::with_label("History");
+    history_button.add_css_class("pill");
+    history_button.set_margin_top(8);
+    history_button.set_margin_bottom(8);
+    history_button.set_margin_start(8);
+    history_button.set_margin_end(8);
+    history_button.set_height_request(42);
+    history_button.set_width_request(120);
+    history_button.set_tooltip_text(Some("Restore the files from a backup taken before saving"));
+    
+    let window_clone = window.clone();
+    let config_dir_clone = config_dir.clone();
+    let live_preview_clone = live_preview.clone();
+    history_button.connect_clicked(move |_| {
+        show_history_window(&window_clone, &config_dir_clone, &live_preview_clone);
+    });
+    
+    bottom_box.append(&history_button);
+    bottom_box.append(&preview_button);
+    bottom_box.append(&revert_button);
     bottom_box.append(&save_button);
+    
+    preview_status.add_css_class("dim-label");
+    preview_status.set_ellipsize(gtk4::pango::EllipsizeMode::End);
+    main_box.append(&preview_status);
     main_box.append(&bottom_box);
     
+    // Connect to Hyprland when previewing starts and push the edits made so far
+    let window_clone = window.clone();
+    let live_preview_clone = live_preview.clone();
+    let edited_values_clone = edited_values.clone();
+    let revert_button_clone = revert_button.clone();
+    let preview_status_clone = preview_status.clone();
+    preview_button.connect_toggled(move |button| {
+        if !button.is_active() {
+            if live_preview_clone.borrow().as_ref().is_some_and(|preview| preview.has_changes()) {
+                preview_status_clone.set_text("Preview stopped, Hyprland keeps the previewed values until you revert or save");
+            } else {
+                preview_status_clone.set_text("");
+            }
+            return;
+        }
+        
+        if live_preview_clone.borrow().is_none() {
+            match ipc::HyprlandIpc::from_env() {
+                Ok(ipc) => {
+                    *live_preview_clone.borrow_mut() = Some(ipc::LivePreview::new(ipc));
+                }
+                Err(err) => {
+                    button.set_active(false);
+                    show_error_dialog(&window_clone, &format!("Cannot preview changes: {}", err));
+                    return;
+                }
+            }
+        }
+        
+        preview_status_clone.set_text("Previewing changes in Hyprland");
+        for (option, value) in edited_values_clone.borrow().iter() {
+            preview_value(&live_preview_clone, option, value, &preview_status_clone, &revert_button_clone);
+        }
+    });
+    
+    // Restore the runtime values captured before previewing
+    let window_clone = window.clone();
+    let live_preview_clone = live_preview.clone();
+    let preview_button_clone = preview_button.clone();
+    let preview_status_clone = preview_status.clone();
+    let check_runtime_clone = check_runtime.clone();
+    revert_button.connect_clicked(move |button| {
+        // Stop previewing first so the restored values are not pushed again
+        preview_button_clone.set_active(false);
+        
+        let result = match live_preview_clone.borrow_mut().as_mut() {
+            Some(preview) => preview.revert(),
+            None => Ok(0),
+        };
+        button.set_sensitive(false);
+        
+        match result {
+            Ok(count) => preview_status_clone.set_text(&format!("Restored {} options in Hyprland, your edits are kept until you save", count)),
+            Err(err) => show_error_dialog(&window_clone, &format!("Failed to restore some options:\n{}", err)),
+        }
+        check_runtime_clone();
+    });
+    
     // Set up the save button click handler
     let config_dir_clone = config_dir.clone();
     let window_clone = window.clone();
     let changes_clone = changes.clone();
     let variables_clone = variables.clone();
     
+    let invalid_clone = invalid.clone();
+    let live_preview_clone = live_preview.clone();
+    let revert_button_clone = revert_button.clone();
+    let check_runtime_clone = check_runtime.clone();
+    let file_hashes_clone = file_hashes.clone();
+    
     save_button.connect_clicked(move |_button| {
+        // Refuse to write values the schema rejects
+        let mut errors: Vec<String> = invalid_clone.borrow().values().cloned().collect();
+        if !errors.is_empty() {
+            errors.sort();
+            show_error_dialog(&window_clone, &format!("Some values are invalid:\n\n{}", errors.join("\n")));
+            return;
+        }
+        
         let changes_ref = changes_clone.borrow();
         
         // Always proceed with saving, regardless of whether changes_ref is empty
@@ -919,17 +1607,22 @@ fn load_application_content(window: &ApplicationWindow) {
         let window_clone2 = window_clone.clone();
         let changes_clone2 = changes_clone.clone();
         let variables_clone2 = variables_clone.clone();
+        let live_preview_clone2 = live_preview_clone.clone();
+        let revert_button_clone2 = revert_button_clone.clone();
+        let check_runtime_clone2 = check_runtime_clone.clone();
+        let file_hashes_clone2 = file_hashes_clone.clone();
         
         show_warning_dialog(&window_clone, &msg, Box::new(move || {
-            match save_changes(&changes_vec, &config_dir_clone2) {
-                Ok(_) => {
+            match save_changes(&changes_vec, &config_dir_clone2, &mut file_hashes_clone2.borrow_mut()) {
+                Ok(snapshot) => {
                     // Update the original variable values with the new values
                     let mut vars = variables_clone2.borrow_mut();
                     for (file, line_number, new_value, _) in &changes_vec {
                         for var in vars.iter_mut() {
                             if var.file == *file && var.line_number == *line_number {
-                                if let Some(start_idx) = new_value.find('=') {
-                                    let value = normalize_variable_value(&new_value[start_idx+1..]);
+                                let (code, _) = split_comment(new_value);
+                                if let Some(start_idx) = code.find('=') {
+                                    let value = normalize_variable_value(&code[start_idx+1..]);
                                     var.value = value;
                                     
                                     // Update the original line to match what's in the file now
@@ -940,12 +1633,22 @@ fn load_application_content(window: &ApplicationWindow) {
                         }
                     }
                     
+                    // The saved values are the new baseline for previewing
+                    if let Some(preview) = live_preview_clone2.borrow_mut().as_mut() {
+                        preview.forget();
+                    }
+                    revert_button_clone2.set_sensitive(false);
+                    
+                    // Hyprland reloads the files on its own, check again once it had time to
+                    let check_runtime_clone3 = check_runtime_clone2.clone();
+                    glib::timeout_add_local_once(std::time::Duration::from_secs(1), move || check_runtime_clone3());
+                    
                     // Show success dialog with callback to clear changes
                     let changes_clone3 = changes_clone2.clone();
                     
                     show_success_dialog(
                         &window_clone2, 
-                        "Successfully saved your configuration files.",
+                        &format!("Successfully saved your configuration files.\n\nThe previous versions were backed up to {}", snapshot.path.display()),
                         Box::new(move || {
                             // Clear changes when dialog is closed
                             changes_clone3.borrow_mut().clear();
@@ -961,45 +1664,15 @@ fn load_application_content(window: &ApplicationWindow) {
     
     // Implement search functionality
     let list_boxes_clone = list_boxes.clone();
+    let filter_dropdown_clone = filter_dropdown.clone();
     search_entry.connect_changed(move |entry| {
-        let query = entry.text().to_lowercase();
-        
-        for (_, list_box) in &list_boxes_clone {
-            // In GTK4, we need to get the rows differently
-            for row in list_box.observe_children().snapshot().iter() {
-                if let Some(row) = row.downcast_ref::<gtk4::ListBoxRow>() {
-                    if let Some(hbox) = row.child() {
-                        let mut contains_query = false;
-                        
-                        // Check if the row contains the text in either column
-                        if let Some(box_container) = hbox.downcast_ref::<gtk4::Box>() {
-                            // In GTK4, we iterate through children differently
-                            let mut first_child = box_container.first_child();
-                            while let Some(child) = first_child {
-                                // Check if it's a label (variable name)
-                                if let Some(label) = child.downcast_ref::<gtk4::Label>() {
-                                    let text = label.text().to_string().to_lowercase();
-                                    if text.contains(&query) {
-                                        contains_query = true;
-                                        break;
-                                    }
-                                } else if let Some(entry) = child.downcast_ref::<gtk4::Entry>() {
-                                    let text = entry.text().to_string().to_lowercase();
-                                    if text.contains(&query) {
-                                        contains_query = true;
-                                        break;
-                                    }
-                                }
-                                
-                                first_child = child.next_sibling();
-                            }
-                        }
-                        
-                        row.set_visible(query.is_empty() || contains_query);
-                    }
-                }
-            }
-        }
+        filter_rows(&list_boxes_clone, &entry.text().to_lowercase(), filter_dropdown_clone.selected());
+    });
+    
+    let list_boxes_clone = list_boxes.clone();
+    let search_entry_clone = search_entry.clone();
+    filter_dropdown.connect_selected_notify(move |dropdown| {
+        filter_rows(&list_boxes_clone, &search_entry_clone.text().to_lowercase(), dropdown.selected());
     });
     
     // Add the main box to the window
@@ -1063,3 +1736,250 @@ fn main() {
     // Run the application
     app.run();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    /// Write `files` into a temporary config directory, returning it with the hashes of the files
+    fn config_dir(files: &[(&str, &str)]) -> (tempfile::TempDir, HashMap<String, u64>) {
+        backup::tests::use_test_cache_dir();
+        let dir = tempfile::tempdir().unwrap();
+        let mut hashes = HashMap::new();
+        for (name, content) in files {
+            fs::write(dir.path().join(name), content).unwrap();
+            hashes.insert(name.to_string(), backup::content_hash(content));
+        }
+        (dir, hashes)
+    }
+    
+    fn change(file: &str, line_number: usize, new_line: &str, original_line: &str) -> (String, usize, String, String) {
+        (file.to_string(), line_number, new_line.to_string(), original_line.to_string())
+    }
+    
+    /// Parse a main config made of the given lines
+    fn parse(lines: &[&str]) -> Vec<HyprVariable> {
+        let config = format!("{}\n", lines.join("\n"));
+        let (dir, _) = config_dir(&[("hyprland.conf", config.as_str())]);
+        parse_hyprland_configs(dir.path()).0
+    }
+    
+    fn names_and_values(variables: &[HyprVariable]) -> Vec<(&str, &str)> {
+        variables.iter().map(|var| (var.name.as_str(), var.value.as_str())).collect()
+    }
+    
+    #[test]
+    fn parse_nested_sections() {
+        let variables = parse(&[
+            "input {",
+            "    sensitivity = 0",
+            "    touchpad {",
+            "        natural_scroll = true",
+            "    }",
+            "    follow_mouse = 1",
+            "}",
+            "general:gaps_in = 5",
+        ]);
+        assert_eq!(names_and_values(&variables), vec![
+            ("input:sensitivity", "0"),
+            ("input:touchpad:natural_scroll", "true"),
+            ("input:follow_mouse", "1"),
+            ("general:gaps_in", "5"),
+        ]);
+    }
+    
+    #[test]
+    fn parse_colon_paths() {
+        let variables = parse(&[
+            "decoration:blur:size = 3",
+            "decoration {",
+            "    blur:passes = 2",
+            "    col.shadow = rgba(1a1a1aee)",
+            "}",
+        ]);
+        assert_eq!(names_and_values(&variables), vec![
+            ("decoration:blur:size", "3"),
+            ("decoration:blur:passes", "2"),
+            ("decoration:col.shadow", "rgba(1a1a1aee)"),
+        ]);
+    }
+    
+    #[test]
+    fn parse_closing_brace_with_other_text() {
+        let variables = parse(&[
+            "input {",
+            "    touchpad {",
+            "        natural_scroll = true",
+            "    }} # end of input",
+            "general {",
+            "    gaps_in = 5",
+            "} gaps_out = 10",
+        ]);
+        assert_eq!(names_and_values(&variables), vec![
+            ("input:touchpad:natural_scroll", "true"),
+            ("general:gaps_in", "5"),
+            ("gaps_out", "10"),
+        ]);
+    }
+    
+    #[test]
+    fn parse_trailing_comments() {
+        let variables = parse(&[
+            "input {  # Input",
+            "    natural_scroll = false     # Traditional scrolling for mouse",
+            "    kb_layout = us ## not a comment # but this is",
+            "}",
+            "# general:gaps_in = 5",
+        ]);
+        assert_eq!(names_and_values(&variables), vec![
+            ("input:natural_scroll", "false"),
+            ("input:kb_layout", "us # not a comment"),
+        ]);
+        assert_eq!(variables[0].comment, "     # Traditional scrolling for mouse");
+        assert_eq!(variables[1].comment, " # but this is");
+    }
+    
+    #[test]
+    fn rewrite_keeps_comment_and_escapes_hashes() {
+        let variables = parse(&[
+            "input {",
+            "    natural_scroll = false     # Traditional scrolling for mouse",
+            "}",
+            "general:col.active_border = rgba(33ccffee)",
+        ]);
+        assert_eq!(
+            rewrite_variable_line(&variables[0], "true"),
+            "    natural_scroll = true     # Traditional scrolling for mouse"
+        );
+        assert_eq!(rewrite_variable_line(&variables[1], "a#b"), "general:col.active_border = a##b");
+    }
+    
+    #[test]
+    fn commented_values_get_typed_editors() {
+        let variables = parse(&[
+            "input {",
+            "    natural_scroll = false     # Traditional scrolling for mouse",
+            "    sensitivity = 0        # Slightly slower than default",
+            "}",
+            "general {",
+            "    resize_corner = 0  # Disabled",
+            "    layout = master # Tiling",
+            "}",
+        ]);
+        
+        for var in &variables {
+            let option = schema::lookup(&var.name).unwrap();
+            assert_eq!(typed_editor_kind(&var.value, Some(option)), Some(option.kind), "{}", var.name);
+            assert!(option.validate(&var.value).is_ok(), "{}", var.name);
+        }
+        
+        // Values with variables stay text
+        let option = schema::lookup("general:resize_corner");
+        assert_eq!(typed_editor_kind("$corner", option), None);
+    }
+    
+    #[test]
+    fn commented_values_compare_with_runtime_values() {
+        let variables = parse(&[
+            "input {",
+            "    sensitivity = 0        # Slightly slower than default",
+            "    natural_scroll = false # Traditional scrolling",
+            "}",
+        ]);
+        
+        for (var, runtime) in variables.iter().zip(["0", "0"]) {
+            let option = schema::lookup(&var.name).unwrap();
+            assert!(option.is_default(&var.value), "{}", var.name);
+            assert!(schema::same_value(Some(option.kind), runtime, &var.value), "{}", var.name);
+        }
+    }
+    
+    #[test]
+    fn save_keeps_comments() {
+        let config = "input {\n    natural_scroll = false     # Traditional scrolling for mouse\n}\n";
+        let (dir, mut hashes) = config_dir(&[("hyprland.conf", config)]);
+        let variables = parse_hyprland_configs(dir.path()).0;
+        let var = &variables[0];
+        
+        save_changes(&[
+            change("hyprland.conf", var.line_number, &rewrite_variable_line(var, "true"), &var.original_line),
+        ], dir.path(), &mut hashes).unwrap();
+        
+        assert_eq!(
+            fs::read_to_string(dir.path().join("hyprland.conf")).unwrap(),
+            "input {\n    natural_scroll = true     # Traditional scrolling for mouse\n}\n"
+        );
+    }
+    
+    #[test]
+    fn save_keeps_line_endings() {
+        let (dir, mut hashes) = config_dir(&[
+            ("hyprland.conf", "general {\r\n    gaps_in = 5\r\n}\r\n"),
+            ("looks.conf", "decoration:rounding = 10\ndecoration:blur:size = 3"),
+        ]);
+        
+        let snapshot = save_changes(&[
+            change("hyprland.conf", 2, "    gaps_in = 8", "    gaps_in = 5"),
+            change("looks.conf", 2, "decoration:blur:size = 6", "decoration:blur:size = 3"),
+        ], dir.path(), &mut hashes).unwrap();
+        
+        let main = fs::read_to_string(dir.path().join("hyprland.conf")).unwrap();
+        let looks = fs::read_to_string(dir.path().join("looks.conf")).unwrap();
+        assert_eq!(main, "general {\r\n    gaps_in = 8\r\n}\r\n");
+        assert_eq!(looks, "decoration:rounding = 10\ndecoration:blur:size = 6");
+        assert_eq!(hashes["hyprland.conf"], backup::content_hash(&main));
+        assert_eq!(hashes["looks.conf"], backup::content_hash(&looks));
+        assert_eq!(snapshot.files.len(), 2);
+    }
+    
+    #[test]
+    fn save_puts_back_written_files_when_a_write_fails() {
+        let (dir, mut hashes) = config_dir(&[("a.conf", "gaps_in = 5\n"), ("b.conf", "gaps_out = 10\n")]);
+        
+        // The temporary file for b.conf cannot be created, so a.conf is written first and b.conf fails
+        fs::create_dir(dir.path().join(".b.conf.hyprland-settings.tmp")).unwrap();
+        let changes = [
+            change("a.conf", 1, "gaps_in = 8", "gaps_in = 5"),
+            change("b.conf", 1, "gaps_out = 20", "gaps_out = 10"),
+        ];
+        let err = save_changes(&changes, dir.path(), &mut hashes).unwrap_err();
+        
+        assert!(err.starts_with("Failed to write"), "{}", err);
+        assert!(err.ends_with("\nNo file was changed."), "{}", err);
+        assert_eq!(fs::read_to_string(dir.path().join("a.conf")).unwrap(), "gaps_in = 5\n");
+        assert_eq!(fs::read_to_string(dir.path().join("b.conf")).unwrap(), "gaps_out = 10\n");
+        assert_eq!(hashes["a.conf"], backup::content_hash("gaps_in = 5\n"));
+    }
+    
+    #[test]
+    fn save_refuses_files_changed_since_loading() {
+        let (dir, mut hashes) = config_dir(&[
+            ("hyprland.conf", "general:gaps_in = 5\n"),
+            ("looks.conf", "decoration:rounding = 10\n"),
+        ]);
+        // Another program edits one of the files
+        fs::write(dir.path().join("looks.conf"), "decoration:rounding = 12\n").unwrap();
+        
+        let result = save_changes(&[
+            change("hyprland.conf", 1, "general:gaps_in = 8", "general:gaps_in = 5"),
+            change("looks.conf", 1, "decoration:rounding = 4", "decoration:rounding = 10"),
+        ], dir.path(), &mut hashes);
+        
+        assert!(result.unwrap_err().contains("looks.conf was changed"));
+        // Neither file is written
+        assert_eq!(fs::read_to_string(dir.path().join("hyprland.conf")).unwrap(), "general:gaps_in = 5\n");
+        assert_eq!(fs::read_to_string(dir.path().join("looks.conf")).unwrap(), "decoration:rounding = 12\n");
+    }
+    
+    #[test]
+    fn save_refuses_lines_that_no_longer_match() {
+        let (dir, mut hashes) = config_dir(&[("hyprland.conf", "general:gaps_in = 5\n")]);
+        
+        let result = save_changes(&[
+            change("hyprland.conf", 1, "general:gaps_out = 8", "general:gaps_out = 20"),
+        ], dir.path(), &mut hashes);
+        
+        assert!(result.unwrap_err().contains("Line mismatch"));
+        assert_eq!(fs::read_to_string(dir.path().join("hyprland.conf")).unwrap(), "general:gaps_in = 5\n");
+    }
+}
@@ -0,0 +1,451 @@
+// Bundled schema of the Hyprland options the editor knows about.
+// Names use Hyprland's `section:option` form, e.g. `decoration:blur:size`.
+
+use regex::Regex;
+use std::sync::OnceLock;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OptionType {
+    Bool,
+    Int { min: i64, max: i64 },
+    Float { min: f64, max: f64 },
+    /// A single colour, e.g. `rgba(33ccffee)` or `0xff444444`
+    Color,
+    /// One or more colours with an optional angle, e.g. `rgba(33ccffee) rgba(00ff99ee) 45deg`
+    Gradient,
+    /// Two floats separated by a space, e.g. `0 5`
+    Vec2,
+    String,
+    /// One of a fixed set of words
+    Enum(&'static [&'static str]),
+    /// A gap size, either one int or css-style `top,right,bottom,left`
+    Gaps,
+}
+
+pub struct OptionSchema {
+    pub name: &'static str,
+    pub kind: OptionType,
+    pub default: &'static str,
+    pub description: &'static str,
+}
+
+const fn option(name: &'static str, kind: OptionType, default: &'static str, description: &'static str) -> OptionSchema {
+    OptionSchema { name, kind, default, description }
+}
+
+pub static OPTIONS: &[OptionSchema] = {
+    use OptionType::*;
+    &[
+        // general
+        option("general:border_size", Int { min: 0, max: 20 }, "1", "Size of the border around windows"),
+        option("general:gaps_in", Gaps, "5", "Gaps between windows"),
+        option("general:gaps_out", Gaps, "20", "Gaps between windows and the monitor edges"),
+        option("general:gaps_workspaces", Int { min: 0, max: 500 }, "0", "Gaps between workspaces, stacks with gaps_out"),
+        option("general:col.inactive_border", Gradient, "0xff444444", "Border colour of inactive windows"),
+        option("general:col.active_border", Gradient, "0xffffffff", "Border colour of the active window"),
+        option("general:col.nogroup_border", Gradient, "0xffffaaff", "Inactive border colour of windows that cannot be added to a group"),
+        option("general:col.nogroup_border_active", Gradient, "0xffff00ff", "Active border colour of windows that cannot be added to a group"),
+        option("general:layout", Enum(&["dwindle", "master"]), "dwindle", "Layout used to tile windows"),
+        option("general:no_focus_fallback", Bool, "false", "Do not fall back to the next window when moving focus in a direction without one"),
+        option("general:resize_on_border", Bool, "false", "Resize windows by dragging their border or gaps"),
+        option("general:extend_border_grab_area", Int { min: 0, max: 100 }, "15", "Extra area around the border where dragging still resizes"),
+        option("general:hover_icon_on_border", Bool, "true", "Show a resize cursor when hovering the border"),
+        option("general:allow_tearing", Bool, "false", "Allow windows to request tearing"),
+        option("general:resize_corner", Int { min: 0, max: 4 }, "0", "Corner used by floating resizes, 0 picks the closest one"),
+        // decoration
+        option("decoration:rounding", Int { min: 0, max: 100 }, "0", "Corner radius of windows in pixels"),
+        option("decoration:rounding_power", Float { min: 2.0, max: 10.0 }, "2.0", "Curve of the rounded corners, 2 is a circle"),
+        option("decoration:active_opacity", Float { min: 0.0, max: 1.0 }, "1.0", "Opacity of the active window"),
+        option("decoration:inactive_opacity", Float { min: 0.0, max: 1.0 }, "1.0", "Opacity of inactive windows"),
+        option("decoration:fullscreen_opacity", Float { min: 0.0, max: 1.0 }, "1.0", "Opacity of fullscreen windows"),
+        option("decoration:dim_inactive", Bool, "false", "Dim inactive windows"),
+        option("decoration:dim_strength", Float { min: 0.0, max: 1.0 }, "0.5", "How much inactive windows are dimmed"),
+        option("decoration:dim_special", Float { min: 0.0, max: 1.0 }, "0.2", "How much the background is dimmed behind a special workspace"),
+        option("decoration:dim_around", Float { min: 0.0, max: 1.0 }, "0.4", "How much the dimaround window rule dims"),
+        option("decoration:screen_shader", String, "", "Path to a fragment shader applied to the whole screen"),
+        option("decoration:border_part_of_window", Bool, "true", "Count the border as part of the window"),
+        option("decoration:blur:enabled", Bool, "true", "Blur the background of transparent windows"),
+        option("decoration:blur:size", Int { min: 1, max: 100 }, "8", "Blur radius"),
+        option("decoration:blur:passes", Int { min: 1, max: 10 }, "1", "Number of blur passes"),
+        option("decoration:blur:ignore_opacity", Bool, "true", "Blur as if the window was fully opaque"),
+        option("decoration:blur:new_optimizations", Bool, "true", "Faster blur, leave this on"),
+        option("decoration:blur:xray", Bool, "false", "Floating windows blur the wallpaper instead of the windows behind them"),
+        option("decoration:blur:noise", Float { min: 0.0, max: 1.0 }, "0.0117", "Amount of noise added to the blur"),
+        option("decoration:blur:contrast", Float { min: 0.0, max: 2.0 }, "0.8916", "Contrast of the blur"),
+        option("decoration:blur:brightness", Float { min: 0.0, max: 2.0 }, "0.8172", "Brightness of the blur"),
+        option("decoration:blur:vibrancy", Float { min: 0.0, max: 1.0 }, "0.1696", "Saturation boost of blurred colours"),
+        option("decoration:blur:special", Bool, "false", "Blur behind special workspaces, expensive"),
+        option("decoration:blur:popups", Bool, "false", "Blur popups such as context menus"),
+        option("decoration:shadow:enabled", Bool, "true", "Draw shadows behind windows"),
+        option("decoration:shadow:range", Int { min: 0, max: 100 }, "4", "Shadow range in pixels"),
+        option("decoration:shadow:render_power", Int { min: 1, max: 4 }, "3", "How fast the shadow falls off"),
+        option("decoration:shadow:sharp", Bool, "false", "Draw a hard shadow without falloff"),
+        option("decoration:shadow:ignore_window", Bool, "true", "Only draw the shadow outside the window"),
+        option("decoration:shadow:color", Color, "0xee1a1a1a", "Shadow colour"),
+        option("decoration:shadow:offset", Vec2, "0 0", "Shadow offset in pixels"),
+        option("decoration:shadow:scale", Float { min: 0.0, max: 1.0 }, "1.0", "Shadow size relative to the window"),
+        // animations
+        option("animations:enabled", Bool, "true", "Enable animations"),
+        option("animations:first_launch_animation", Bool, "true", "Fade in when Hyprland starts"),
+        // input
+        option("input:kb_layout", String, "us", "Keyboard layouts, comma separated"),
+        option("input:kb_variant", String, "", "Keyboard layout variants"),
+        option("input:kb_model", String, "", "Keyboard model"),
+        option("input:kb_options", String, "", "XKB options, e.g. grp:alt_shift_toggle"),
+        option("input:numlock_by_default", Bool, "false", "Turn numlock on at startup"),
+        option("input:repeat_rate", Int { min: 1, max: 200 }, "25", "Key repeats per second while held"),
+        option("input:repeat_delay", Int { min: 0, max: 5000 }, "600", "Delay in milliseconds before a held key repeats"),
+        option("input:sensitivity", Float { min: -1.0, max: 1.0 }, "0.0", "Pointer sensitivity"),
+        option("input:accel_profile", Enum(&["adaptive", "flat", "custom"]), "", "Pointer acceleration profile, empty for the libinput default"),
+        option("input:follow_mouse", Int { min: 0, max: 3 }, "1", "How keyboard focus follows the mouse"),
+        option("input:natural_scroll", Bool, "false", "Invert mouse scrolling"),
+        option("input:left_handed", Bool, "false", "Swap the left and right mouse buttons"),
+        option("input:scroll_method", Enum(&["2fg", "edge", "on_button_down", "no_scroll"]), "", "How scrolling is triggered, empty for the libinput default"),
+        option("input:touchpad:natural_scroll", Bool, "false", "Invert touchpad scrolling"),
+        option("input:touchpad:disable_while_typing", Bool, "true", "Ignore the touchpad while typing"),
+        option("input:touchpad:tap-to-click", Bool, "true", "Tap the touchpad to click"),
+        option("input:touchpad:scroll_factor", Float { min: 0.0, max: 10.0 }, "1.0", "Multiplier for touchpad scroll distance"),
+        option("input:touchpad:clickfinger_behavior", Bool, "false", "Click with 1, 2 or 3 fingers for left, right and middle click"),
+        option("input:touchpad:drag_lock", Bool, "false", "Keep dragging after lifting the finger briefly"),
+        // gestures
+        option("gestures:workspace_swipe", Bool, "false", "Swipe between workspaces on the touchpad"),
+        option("gestures:workspace_swipe_fingers", Int { min: 2, max: 5 }, "3", "Fingers used to swipe between workspaces"),
+        option("gestures:workspace_swipe_distance", Int { min: 0, max: 5000 }, "300", "Swipe distance in pixels for one workspace"),
+        option("gestures:workspace_swipe_invert", Bool, "true", "Invert the swipe direction"),
+        option("gestures:workspace_swipe_create_new", Bool, "true", "Create a new workspace when swiping past the last one"),
+        option("gestures:workspace_swipe_forever", Bool, "false", "Keep swiping past the neighbouring workspace"),
+        // misc
+        option("misc:disable_hyprland_logo", Bool, "false", "Hide the Hyprland logo on the default wallpaper"),
+        option("misc:disable_splash_rendering", Bool, "false", "Hide the splash text on the default wallpaper"),
+        option("misc:force_default_wallpaper", Int { min: -1, max: 2 }, "-1", "Default wallpaper to use, -1 picks one at random"),
+        option("misc:vfr", Bool, "true", "Only render frames when something changes"),
+        option("misc:vrr", Int { min: 0, max: 3 }, "0", "Adaptive sync: 0 off, 1 on, 2 fullscreen only, 3 fullscreen games only"),
+        option("misc:mouse_move_enables_dpms", Bool, "false", "Moving the mouse turns the displays back on"),
+        option("misc:key_press_enables_dpms", Bool, "false", "Pressing a key turns the displays back on"),
+        option("misc:always_follow_on_dnd", Bool, "true", "Focus follows the mouse while dragging"),
+        option("misc:focus_on_activate", Bool, "false", "Focus windows that request activation"),
+        option("misc:animate_manual_resizes", Bool, "false", "Animate resizes done with the mouse"),
+        option("misc:enable_swallow", Bool, "false", "Let terminals swallow the windows they open"),
+        option("misc:swallow_regex", String, "", "Class regex of the windows that swallow"),
+        option("misc:background_color", Color, "0xff111111", "Colour behind the wallpaper"),
+        option("misc:font_family", String, "Sans", "Font used by Hyprland's own UI"),
+        option("misc:middle_click_paste", Bool, "true", "Paste the primary selection on middle click"),
+        // layouts
+        option("dwindle:pseudotile", Bool, "false", "Enable pseudotiling"),
+        option("dwindle:force_split", Int { min: 0, max: 2 }, "0", "Split direction: 0 follows the mouse, 1 left or top, 2 right or bottom"),
+        option("dwindle:preserve_split", Bool, "false", "Keep the split direction when the layout changes"),
+        option("dwindle:smart_split", Bool, "false", "Split based on the cursor position within the window"),
+        option("dwindle:special_scale_factor", Float { min: 0.0, max: 1.0 }, "1.0", "Scale of windows on special workspaces"),
+        option("dwindle:split_width_multiplier", Float { min: 0.1, max: 3.0 }, "1.0", "Prefer vertical splits on wide windows by this factor"),
+        option("master:new_status", Enum(&["master", "slave", "inherit"]), "slave", "Where new windows are placed"),
+        option("master:new_on_top", Bool, "false", "Put new windows at the top of the stack"),
+        option("master:mfact", Float { min: 0.0, max: 1.0 }, "0.55", "Size of the master area"),
+        option("master:orientation", Enum(&["left", "right", "top", "bottom", "center"]), "left", "Side of the master area"),
+        // cursor and xwayland
+        option("cursor:inactive_timeout", Float { min: 0.0, max: 3600.0 }, "0", "Hide the cursor after this many seconds, 0 never hides it"),
+        option("cursor:hide_on_key_press", Bool, "false", "Hide the cursor while typing"),
+        option("cursor:no_warps", Bool, "false", "Never move the cursor on focus changes"),
+        option("xwayland:enabled", Bool, "true", "Run XWayland for X11 applications"),
+        option("xwayland:force_zero_scaling", Bool, "false", "Do not scale XWayland windows"),
+    ]
+};
+
+/// Find the schema of an option by its `section:option` name
+pub fn lookup(name: &str) -> Option<&'static OptionSchema> {
+    OPTIONS.iter().find(|option| option.name == name)
+}
+
+/// Keywords that add an entry every time they appear instead of setting an
+/// option, e.g. `bind` or `animations { bezier = ... }`
+const REPEATABLE_KEYWORDS: &[&str] = &[
+    "animation", "bezier", "env", "envd", "exec", "exec-once", "exec-shutdown", "execr", "execr-once",
+    "gesture", "layerrule", "monitor", "permission", "plugin", "source", "submap", "unbind", "windowrule",
+    "windowrulev2", "workspace",
+];
+
+/// Whether a name from the config is an option holding a single value, so a
+/// later definition overrides an earlier one and Hyprland can report it. That
+/// is a known option or a `section:option` name, but not a repeatable keyword
+/// or the settings of one `device { }` block.
+pub fn is_option(name: &str) -> bool {
+    let keyword = name.rsplit(':').next().unwrap_or(name);
+    if REPEATABLE_KEYWORDS.contains(&keyword) || keyword.starts_with("bind") || name.starts_with("device:") {
+        return false;
+    }
+    lookup(name).is_some() || name.contains(':')
+}
+
+/// Read a boolean the way Hyprland does
+pub fn parse_bool(value: &str) -> Option<bool> {
+    match value.trim().to_lowercase().as_str() {
+        "true" | "yes" | "on" | "1" => Some(true),
+        "false" | "no" | "off" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+/// Parse a colour into RGBA bytes. Accepts `rgba(RRGGBBAA)`, `rgb(RRGGBB)`,
+/// `0xAARRGGBB` and the decimal forms `rgba(r, g, b, a)` and `rgb(r, g, b)`.
+pub fn parse_color(value: &str) -> Option<[u8; 4]> {
+    let value = value.trim();
+    let hex = |text: &str| u32::from_str_radix(text, 16).ok();
+    
+    if let Some(argb) = value.strip_prefix("0x") {
+        let argb = hex(argb).filter(|_| argb.len() == 8)?;
+        let [a, r, g, b] = argb.to_be_bytes();
+        return Some([r, g, b, a]);
+    }
+    
+    let (alpha, inner) = match value.strip_prefix("rgba(").or_else(|| value.strip_prefix("rgb(")) {
+        Some(rest) => (value.starts_with("rgba("), rest.strip_suffix(')')?.trim()),
+        None => return None,
+    };
+    
+    // Hex form without separators
+    if !inner.contains(',') {
+        return match (alpha, inner.len()) {
+            (true, 8) => Some(hex(inner)?.to_be_bytes()),
+            (false, 6) => {
+                let [_, r, g, b] = hex(inner)?.to_be_bytes();
+                Some([r, g, b, 255])
+            }
+            _ => None,
+        };
+    }
+    
+    // Decimal form, the alpha channel is a float from 0 to 1
+    let parts: Vec<&str> = inner.split(',').map(str::trim).collect();
+    if parts.len() != if alpha { 4 } else { 3 } {
+        return None;
+    }
+    let channel = |part: &str| part.parse::<u8>().ok();
+    let a = match parts.get(3) {
+        Some(part) => {
+            let a: f64 = part.parse().ok()?;
+            if !(0.0..=1.0).contains(&a) {
+                return None;
+            }
+            (a * 255.0).round() as u8
+        }
+        None => 255,
+    };
+    Some([channel(parts[0])?, channel(parts[1])?, channel(parts[2])?, a])
+}
+
+/// Parse a colour that may come from `getoption`, which reports colours as
+/// plain ARGB integers. Config values are checked with `parse_color` instead.
+pub fn parse_runtime_color(value: &str) -> Option<[u8; 4]> {
+    match value.trim().parse::<u32>() {
+        Ok(argb) => {
+            let [a, r, g, b] = argb.to_be_bytes();
+            Some([r, g, b, a])
+        }
+        Err(_) => parse_color(value),
+    }
+}
+
+/// Write RGBA bytes as `rgba(RRGGBBAA)`
+pub fn format_color([r, g, b, a]: [u8; 4]) -> String {
+    format!("rgba({:02x}{:02x}{:02x}{:02x})", r, g, b, a)
+}
+
+/// Splits a gradient into tokens, compiled on first use
+static GRADIENT_TOKEN_REGEX: OnceLock<Regex> = OnceLock::new();
+
+/// Split a gradient into its colour tokens and the optional angle token
+fn gradient_tokens(value: &str) -> (Vec<&str>, Option<&str>) {
+    let token_regex = GRADIENT_TOKEN_REGEX
+        .get_or_init(|| Regex::new(r"rgba?\([^)]*\)|0x[0-9a-fA-F]+|-?\d+deg|\S+").unwrap());
+    let mut tokens: Vec<&str> = token_regex.find_iter(value).map(|m| m.as_str()).collect();
+    
+    let angle = match tokens.last() {
+        Some(last) if last.ends_with("deg") => tokens.pop(),
+        _ => None,
+    };
+    (tokens, angle)
+}
+
+/// Parse a gradient into its colours and angle in degrees
+pub fn parse_gradient(value: &str) -> Option<(Vec<[u8; 4]>, i64)> {
+    let (colors, angle) = gradient_tokens(value);
+    let colors = colors.iter().map(|color| parse_color(color)).collect::<Option<Vec<_>>>()?;
+    let angle = match angle {
+        Some(angle) => angle.trim_end_matches("deg").parse().ok()?,
+        None => 0,
+    };
+    (!colors.is_empty()).then_some((colors, angle))
+}
+
+/// Parse gaps into top, right, bottom and left sizes, expanding the css shorthands
+pub fn parse_gaps(value: &str) -> Option<[u32; 4]> {
+    let sizes = value
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|part| !part.is_empty())
+        .map(|part| part.parse::<u32>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    match sizes[..] {
+        [all] => Some([all; 4]),
+        [vertical, horizontal] => Some([vertical, horizontal, vertical, horizontal]),
+        [top, horizontal, bottom] => Some([top, horizontal, bottom, horizontal]),
+        [top, right, bottom, left] => Some([top, right, bottom, left]),
+        _ => None,
+    }
+}
+
+/// Check a gradient: one or more colours, optionally followed by an angle like `45deg`
+fn validate_gradient(value: &str) -> Result<(), String> {
+    let (colors, angle) = gradient_tokens(value);
+    if colors.is_empty() {
+        return Err("expected at least one colour".to_string());
+    }
+    if let Some(color) = colors.iter().find(|color| parse_color(color).is_none()) {
+        return Err(format!("'{}' is not a colour", color));
+    }
+    if let Some(angle) = angle {
+        angle.trim_end_matches("deg").parse::<i64>().map_err(|_| format!("'{}' is not an angle", angle))?;
+    }
+    Ok(())
+}
+
+impl OptionSchema {
+    /// Check a value against the type and range of the option
+    pub fn validate(&self, value: &str) -> Result<(), String> {
+        use OptionType::*;
+        let value = value.trim();
+        match self.kind {
+            Bool => parse_bool(value).map(|_| ()).ok_or_else(|| "expected true or false".to_string()),
+            Int { min, max } => match value.parse::<i64>() {
+                Ok(number) if (min..=max).contains(&number) => Ok(()),
+                Ok(_) => Err(format!("must be between {} and {}", min, max)),
+                Err(_) => Err("expected a whole number".to_string()),
+            },
+            Float { min, max } => match value.parse::<f64>() {
+                Ok(number) if (min..=max).contains(&number) => Ok(()),
+                Ok(_) => Err(format!("must be between {} and {}", min, max)),
+                Err(_) => Err("expected a number".to_string()),
+            },
+            Color => parse_color(value).map(|_| ()).ok_or_else(|| "expected a colour like rgba(33ccffee)".to_string()),
+            Gradient => validate_gradient(value),
+            Vec2 => {
+                let parts: Vec<&str> = value.split_whitespace().collect();
+                if parts.len() == 2 && parts.iter().all(|part| part.parse::<f64>().is_ok()) {
+                    Ok(())
+                } else {
+                    Err("expected two numbers like '0 5'".to_string())
+                }
+            }
+            OptionType::String => Ok(()),
+            Enum(values) => {
+                if values.contains(&value) || value == self.default {
+                    Ok(())
+                } else {
+                    Err(format!("expected one of {}", values.join(", ")))
+                }
+            }
+            Gaps => parse_gaps(value).map(|_| ()).ok_or_else(|| "expected a size or top,right,bottom,left sizes".to_string()),
+        }
+    }
+    
+    /// Check whether a value means the same as the default, e.g. `yes` and `true`
+    pub fn is_default(&self, value: &str) -> bool {
+        same_value(Some(self.kind), value, self.default)
+    }
+}
+
+/// Check whether two values of an option mean the same, e.g. `yes` and `1` or
+/// `rgba(33ccffee)` and `0xee33ccff`. Without a type the values are compared
+/// as numbers, booleans or colours when both read as one. Either value may be
+/// a runtime value from `getoption`.
+pub fn same_value(kind: Option<OptionType>, a: &str, b: &str) -> bool {
+    let (a, b) = (a.trim(), b.trim());
+    
+    // Runtime floats are single precision, so allow a little rounding
+    let close = |x: f64, y: f64| (x - y).abs() <= 1e-4 * y.abs().max(1.0);
+    let numbers = |text: &str| text.split_whitespace().map(|part| part.parse::<f64>().ok()).collect::<Option<Vec<_>>>();
+    
+    match kind {
+        Some(OptionType::Bool) => parse_bool(a).is_some() && parse_bool(a) == parse_bool(b),
+        Some(OptionType::Int { .. } | OptionType::Float { .. }) => match (a.parse::<f64>(), b.parse::<f64>()) {
+            (Ok(x), Ok(y)) => close(x, y),
+            _ => a == b,
+        },
+        Some(OptionType::Color) => parse_runtime_color(a).is_some() && parse_runtime_color(a) == parse_runtime_color(b),
+        Some(OptionType::Gradient) => parse_gradient(a).is_some() && parse_gradient(a) == parse_gradient(b),
+        Some(OptionType::Gaps) => parse_gaps(a).is_some() && parse_gaps(a) == parse_gaps(b),
+        Some(OptionType::Vec2) => match (numbers(a), numbers(b)) {
+            (Some(x), Some(y)) => x.len() == y.len() && x.iter().zip(&y).all(|(x, y)| close(*x, *y)),
+            _ => a == b,
+        },
+        Some(OptionType::String | OptionType::Enum(_)) => a == b,
+        None => {
+            if let (Ok(x), Ok(y)) = (a.parse::<f64>(), b.parse::<f64>()) {
+                close(x, y)
+            } else if let (Some(x), Some(y)) = (parse_bool(a), parse_bool(b)) {
+                x == y
+            } else if let (Some(x), Some(y)) = (parse_gradient(a), parse_gradient(b)) {
+                x == y
+            } else {
+                a == b
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn validate_rejects_plain_numbers_as_colours() {
+        let color = lookup("decoration:shadow:color").unwrap();
+        assert!(color.validate("rgba(1a1a1aee)").is_ok());
+        assert!(color.validate("0xee1a1a1a").is_ok());
+        assert!(color.validate("rgb(26, 26, 26)").is_ok());
+        assert!(color.validate("5").is_err());
+        assert!(color.validate("3994688026").is_err());
+        
+        let gradient = lookup("general:col.active_border").unwrap();
+        assert!(gradient.validate("rgba(33ccffee) rgba(00ff99ee) 45deg").is_ok());
+        assert!(gradient.validate("5 45deg").is_err());
+    }
+    
+    #[test]
+    fn repeatable_keywords_are_not_options() {
+        assert!(is_option("general:gaps_in"));
+        assert!(is_option("decoration:blur:size"));
+        assert!(is_option("plugin:hyprexpo:columns"));
+        for keyword in ["bind", "bindm", "binde", "exec-once", "windowrulev2", "monitor", "env", "workspace", "source"] {
+            assert!(!is_option(keyword), "{}", keyword);
+        }
+        assert!(!is_option("animations:bezier"));
+        assert!(!is_option("animations:animation"));
+        assert!(!is_option("device:sensitivity"));
+        // Top level names without a section are not options Hyprland knows
+        assert!(!is_option("mainMod"));
+    }
+    
+    #[test]
+    fn runtime_colours_compare_with_config_colours() {
+        let kind = Some(OptionType::Color);
+        assert!(same_value(kind, "3994688026", "rgba(1a1a1aee)"));
+        assert!(same_value(kind, "0xee1a1a1a", "rgba(1a1a1aee)"));
+        assert!(!same_value(kind, "3994688026", "rgba(1a1a1aff)"));
+        assert_eq!(parse_color("3994688026"), None);
+        assert_eq!(parse_runtime_color("3994688026"), Some([0x1a, 0x1a, 0x1a, 0xee]));
+    }
+    
+    #[test]
+    fn values_compare_with_runtime_values_and_defaults() {
+        let sensitivity = lookup("input:sensitivity").unwrap();
+        assert!(sensitivity.is_default("0"));
+        assert!(same_value(Some(sensitivity.kind), "0", "0"));
+        assert!(!same_value(Some(sensitivity.kind), "-0.5", "0"));
+        
+        // Comments are split off before values are compared
+        assert!(!sensitivity.is_default("0 # Slightly slower than default"));
+        
+        let natural_scroll = lookup("input:natural_scroll").unwrap();
+        assert!(natural_scroll.is_default("false"));
+        assert!(same_value(Some(natural_scroll.kind), "0", "false"));
+    }
+}
@@ -0,0 +1,74 @@
+use gtk::gdk;
+use gtk::prelude::*;
+use log::debug;
+
+use crate::config_parser::Keybind;
+use crate::modifiers::{modifiers_from_mask, modmask_from_modifiers};
+
+/// Keys that only act as modifiers and never finish a combination
+const MODIFIER_KEYS: &[&str] = &[
+    "Shift_L", "Shift_R", "Control_L", "Control_R", "Alt_L", "Alt_R", "Meta_L", "Meta_R",
+    "Super_L", "Super_R", "Hyper_L", "Hyper_R", "Caps_Lock", "Num_Lock", "ISO_Level3_Shift",
+    "ISO_Level5_Shift",
+];
+
+/// A key combination pressed while capture mode is active
+#[derive(Debug, Clone, PartialEq)]
+pub struct CapturedCombo {
+    /// Hyprland modmask of the held modifiers
+    pub modmask: u32,
+    /// Keysym name without modifiers applied, like Hyprland resolves binds (`q`, not `Q`)
+    pub key: String,
+    /// Hardware keycode, for binds written as `code:NN`
+    pub keycode: u32,
+}
+
+impl CapturedCombo {
+    /// Build the combo from a key press, or `None` for a lone modifier key
+    pub fn from_key_event(keyval: gdk::Key, keycode: u32, group: u32, state: gdk::ModifierType) -> Option<Self> {
+        let name = keyval.name()?;
+        if MODIFIER_KEYS.contains(&name.as_str()) {
+            return None;
+        }
+        
+        // Use the keysym of the first shift level so SHIFT + 1 is `1` and not `exclam`
+        let base_keyval = gdk::Display::default()
+            .and_then(|display| display.map_keycode(keycode))
+            .and_then(|entries| {
+                entries
+                    .into_iter()
+                    .find(|(key, _)| key.level() == 0 && key.group() as u32 == group)
+                    .map(|(_, keyval)| keyval)
+            })
+            .unwrap_or_else(|| keyval.to_lower());
+        let key = base_keyval.name().map(|name| name.to_string()).unwrap_or_else(|| name.to_string());
+        
+        let mut modmask = 0;
+        if state.contains(gdk::ModifierType::SHIFT_MASK) { modmask |= 1 << 0; }
+        if state.contains(gdk::ModifierType::CONTROL_MASK) { modmask |= 1 << 2; }
+        if state.contains(gdk::ModifierType::ALT_MASK) { modmask |= 1 << 3; }
+        if state.contains(gdk::ModifierType::SUPER_MASK) { modmask |= 1 << 6; }
+        
+        debug!("Captured modmask {} key {} (keycode {})", modmask, key, keycode);
+        Some(Self { modmask, key, keycode })
+    }
+    
+    /// The combo in the same notation as the keybind cards, e.g. `SUPER + SHIFT + Q`
+    pub fn text(&self) -> String {
+        let mut parts = modifiers_from_mask(self.modmask);
+        parts.push(if self.key.chars().count() == 1 { self.key.to_uppercase() } else { self.key.clone() });
+        parts.join(" + ")
+    }
+    
+    /// Check whether a keybind triggers on this combo
+    pub fn matches(&self, keybind: &Keybind) -> bool {
+        if modmask_from_modifiers(&keybind.modifiers) != self.modmask {
+            return false;
+        }
+        
+        match keybind.key.strip_prefix("code:") {
+            Some(code) => code.trim().parse::<u32>().ok() == Some(self.keycode),
+            None => keybind.key.eq_ignore_ascii_case(&self.key),
+        }
+    }
+}
@@ -0,0 +1,287 @@
+use anyhow::{anyhow, Context as _, Result};
+use cairo::{Context, FontSlant, FontWeight, Format, ImageSurface, PdfSurface, SvgSurface};
+use log::info;
+use std::fs::File;
+use std::path::Path;
+
+use crate::config_parser::Keybind;
+use crate::dispatchers::DispatcherCategory;
+use crate::theme;
+
+/// A4 landscape in points
+const PAGE_WIDTH: f64 = 842.0;
+const PAGE_HEIGHT: f64 = 595.0;
+const MARGIN: f64 = 32.0;
+const COLUMNS: usize = 3;
+const COLUMN_GAP: f64 = 18.0;
+/// Print sizes are smaller than the on-screen CSS sizes but keep their proportions
+const PRINT_SCALE: f64 = 0.6;
+/// Space reserved for the sheet title at the top of every page
+const TITLE_HEIGHT: f64 = 36.0;
+/// PNG exports are rendered at twice the point size
+const PNG_SCALE: f64 = 2.0;
+
+/// File formats the cheat sheet can be exported to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SheetFormat {
+    Pdf,
+    Svg,
+    Png,
+}
+
+impl SheetFormat {
+    /// Pick the format from the file extension
+    pub fn from_path(path: &Path) -> Result<Self> {
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_lowercase)
+            .unwrap_or_default();
+        
+        match extension.as_str() {
+            "pdf" => Ok(SheetFormat::Pdf),
+            "svg" => Ok(SheetFormat::Svg),
+            "png" => Ok(SheetFormat::Png),
+            _ => Err(anyhow!("Unsupported cheat sheet format '{}', use .pdf, .svg or .png", extension)),
+        }
+    }
+}
+
+/// An element of the sheet, laid out top to bottom in columns
+enum SheetItem {
+    /// A submap, or the global keymap
+    Header(String),
+    /// A dispatcher category within a submap
+    Subheader(String),
+    Row { combo: String, text: String },
+}
+
+impl SheetItem {
+    fn height(&self) -> f64 {
+        match self {
+            SheetItem::Header(_) => theme::SECTION_TITLE_SIZE * PRINT_SCALE + 12.0,
+            SheetItem::Subheader(_) => theme::ACTION_SIZE * PRINT_SCALE + 8.0,
+            SheetItem::Row { .. } => theme::COMBO_SIZE * PRINT_SCALE + 6.0,
+        }
+    }
+}
+
+/// Items of one column
+type Column<'a> = Vec<&'a SheetItem>;
+
+/// Group keybinds by submap and, within each submap, by dispatcher category
+/// like the sections of the window
+fn sheet_items(keybinds: &[Keybind]) -> Vec<SheetItem> {
+    let mut submaps: Vec<Option<&str>> = vec![None];
+    for keybind in keybinds {
+        if !submaps.contains(&keybind.submap.as_deref()) {
+            submaps.push(keybind.submap.as_deref());
+        }
+    }
+    
+    let mut items = Vec::new();
+    for submap in submaps {
+        let group: Vec<&Keybind> = keybinds.iter().filter(|k| k.submap.as_deref() == submap).collect();
+        if group.is_empty() {
+            continue;
+        }
+        
+        items.push(SheetItem::Header(submap.unwrap_or("Global").to_string()));
+        for category in DispatcherCategory::ALL {
+            let rows: Vec<&Keybind> = group.iter().copied().filter(|k| k.category() == category).collect();
+            if rows.is_empty() {
+                continue;
+            }
+            
+            items.push(SheetItem::Subheader(category.name().to_string()));
+            for keybind in rows {
+                items.push(SheetItem::Row {
+                    combo: keybind.combo_text(),
+                    text: keybind.summary(),
+                });
+            }
+        }
+    }
+    
+    items
+}
+
+/// Distribute items into columns of at most `column_height`, so that
+/// headers never end up without a row at the bottom of a column
+fn layout_columns(items: &[SheetItem], column_height: f64) -> Vec<Column<'_>> {
+    let mut columns: Vec<Column> = vec![Vec::new()];
+    let mut used = 0.0;
+    
+    for (idx, item) in items.iter().enumerate() {
+        // Headers move along with the headers and first row that follow them
+        let mut needed = item.height();
+        if !matches!(item, SheetItem::Row { .. }) {
+            for next in &items[idx + 1..] {
+                needed += next.height();
+                if matches!(next, SheetItem::Row { .. }) {
+                    break;
+                }
+            }
+        }
+        
+        if used + needed > column_height && columns.last().is_some_and(|column| !column.is_empty()) {
+            columns.push(Vec::new());
+            used = 0.0;
+        }
+        
+        if let Some(column) = columns.last_mut() {
+            column.push(item);
+        }
+        used += item.height();
+    }
+    
+    columns
+}
+
+/// Shorten text with an ellipsis until it fits into `width`
+fn fit_text(cr: &Context, text: &str, width: f64) -> Result<String> {
+    if cr.text_extents(text)?.x_advance() <= width {
+        return Ok(text.to_string());
+    }
+    
+    let mut chars: Vec<char> = text.chars().collect();
+    while !chars.is_empty() {
+        chars.pop();
+        let candidate = format!("{}…", chars.iter().collect::<String>());
+        if cr.text_extents(&candidate)?.x_advance() <= width {
+            return Ok(candidate);
+        }
+    }
+    
+    Ok(String::new())
+}
+
+/// Draw the title and the given columns onto one page
+fn draw_page(cr: &Context, columns: &[Column], page_width: f64) -> Result<()> {
+    let column_width = (page_width - 2.0 * MARGIN - (COLUMNS - 1) as f64 * COLUMN_GAP) / COLUMNS as f64;
+    let combo_width = column_width * 0.45;
+    
+    // White background so PNG exports are readable in any viewer
+    cr.set_source_rgb(1.0, 1.0, 1.0);
+    cr.paint()?;
+    
+    cr.set_source_rgb(0.1, 0.1, 0.1);
+    cr.select_font_face("Sans", FontSlant::Normal, FontWeight::Bold);
+    cr.set_font_size(theme::HEADER_SUBTITLE_SIZE * PRINT_SCALE * 1.2);
+    cr.move_to(MARGIN, MARGIN + theme::HEADER_SUBTITLE_SIZE * PRINT_SCALE);
+    cr.show_text("Serial Design V Keybinds")?;
+    
+    let (accent_r, accent_g, accent_b) = theme::rgb(theme::ACCENT_COLOR);
+    
+    for (column_idx, column) in columns.iter().enumerate() {
+        let x = MARGIN + column_idx as f64 * (column_width + COLUMN_GAP);
+        let mut y = MARGIN + TITLE_HEIGHT;
+        
+        for item in column {
+            match item {
+                SheetItem::Header(title) => {
+                    let size = theme::SECTION_TITLE_SIZE * PRINT_SCALE;
+                    cr.set_source_rgb(accent_r, accent_g, accent_b);
+                    cr.select_font_face("Sans", FontSlant::Normal, FontWeight::Bold);
+                    cr.set_font_size(size);
+                    cr.move_to(x, y + size + 4.0);
+                    cr.show_text(title)?;
+                    
+                    // Underline the section title
+                    cr.set_line_width(0.8);
+                    cr.move_to(x, y + size + 8.0);
+                    cr.line_to(x + column_width, y + size + 8.0);
+                    cr.stroke()?;
+                }
+                SheetItem::Subheader(title) => {
+                    let size = theme::ACTION_SIZE * PRINT_SCALE;
+                    cr.set_source_rgba(accent_r, accent_g, accent_b, theme::SECONDARY_OPACITY + 0.15);
+                    cr.select_font_face("Sans", FontSlant::Normal, FontWeight::Bold);
+                    cr.set_font_size(size);
+                    cr.move_to(x, y + size + 2.0);
+                    cr.show_text(title)?;
+                }
+                SheetItem::Row { combo, text } => {
+                    let baseline = y + theme::COMBO_SIZE * PRINT_SCALE;
+                    
+                    cr.set_source_rgb(0.1, 0.1, 0.1);
+                    cr.select_font_face("Sans", FontSlant::Normal, FontWeight::Bold);
+                    cr.set_font_size(theme::ACTION_SIZE * PRINT_SCALE);
+                    let combo = fit_text(cr, combo, combo_width - 4.0)?;
+                    cr.move_to(x, baseline);
+                    cr.show_text(&combo)?;
+                    
+                    cr.set_source_rgba(0.1, 0.1, 0.1, theme::SECONDARY_OPACITY + 0.15);
+                    cr.select_font_face("Sans", FontSlant::Normal, FontWeight::Normal);
+                    let text = fit_text(cr, text, column_width - combo_width)?;
+                    cr.move_to(x + combo_width, baseline);
+                    cr.show_text(&text)?;
+                }
+            }
+            y += item.height();
+        }
+    }
+    
+    Ok(())
+}
+
+/// Render the keybinds as a multi-column cheat sheet. The format is taken
+/// from the file extension (.pdf, .svg or .png).
+pub fn export_cheat_sheet(keybinds: &[Keybind], path: &Path) -> Result<()> {
+    let format = SheetFormat::from_path(path)?;
+    let items = sheet_items(keybinds);
+    let column_height = PAGE_HEIGHT - 2.0 * MARGIN - TITLE_HEIGHT;
+    
+    match format {
+        SheetFormat::Pdf => {
+            // Fixed page size, continue on new pages when the columns are full
+            let surface = PdfSurface::new(PAGE_WIDTH, PAGE_HEIGHT, path)
+                .with_context(|| format!("Failed to create PDF: {:?}", path))?;
+            let cr = Context::new(&surface)?;
+            let columns = layout_columns(&items, column_height);
+            for page in columns.chunks(COLUMNS) {
+                draw_page(&cr, page, PAGE_WIDTH)?;
+                cr.show_page()?;
+            }
+            drop(cr);
+            surface.finish();
+        }
+        SheetFormat::Svg | SheetFormat::Png => {
+            // Single image that grows vertically to fit all columns
+            let total: f64 = items.iter().map(SheetItem::height).sum();
+            let mut height = column_height;
+            let mut columns = layout_columns(&items, height);
+            while columns.len() > COLUMNS {
+                height = height.max(total / COLUMNS as f64) + 20.0;
+                columns = layout_columns(&items, height);
+            }
+            let page_height = height + 2.0 * MARGIN + TITLE_HEIGHT;
+            
+            if format == SheetFormat::Svg {
+                let surface = SvgSurface::new(PAGE_WIDTH, page_height, Some(path))
+                    .with_context(|| format!("Failed to create SVG: {:?}", path))?;
+                let cr = Context::new(&surface)?;
+                draw_page(&cr, &columns, PAGE_WIDTH)?;
+                drop(cr);
+                surface.finish();
+            } else {
+                let surface = ImageSurface::create(
+                    Format::ARgb32,
+                    (PAGE_WIDTH * PNG_SCALE) as i32,
+                    (page_height * PNG_SCALE) as i32,
+                )?;
+                let cr = Context::new(&surface)?;
+                cr.scale(PNG_SCALE, PNG_SCALE);
+                draw_page(&cr, &columns, PAGE_WIDTH)?;
+                drop(cr);
+                
+                let mut file = File::create(path)
+                    .with_context(|| format!("Failed to create PNG: {:?}", path))?;
+                surface.write_to_png(&mut file)?;
+            }
+        }
+    }
+    
+    info!("Exported cheat sheet with {} keybinds to {:?}", keybinds.len(), path);
+    Ok(())
+}
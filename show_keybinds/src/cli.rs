@@ -0,0 +1,389 @@
+use anyhow::{anyhow, bail, Result};
+use log::info;
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::cheatsheet;
+use crate::config_parser::{ConfigParser, Keybind};
+
+const USAGE: &str = "Usage: hyprland-keybinds [OPTIONS]
+
+Without options the keybinds window is opened.
+
+The config file is taken from --config, $HYPRLAND_CONFIG, the file the
+running Hyprland was started with, or ~/.config/hypr/hyprland.conf.
+
+Options:
+  --list              Print the keybinds to stdout instead of opening a window
+  --format <FORMAT>   Output format for --list: plain, json, markdown or csv (implies --list)
+  --cheat-sheet <FILE>
+                      Export a printable cheat sheet (.pdf, .svg or .png) and exit
+  --config <FILE>     Read this config file instead of discovering it
+  -h, --help          Show this help";
+
+/// Output formats for the headless `--list` mode
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    Plain,
+    Json,
+    Markdown,
+    Csv,
+}
+
+impl FromStr for OutputFormat {
+    type Err = anyhow::Error;
+    
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "plain" | "text" => Ok(OutputFormat::Plain),
+            "json" => Ok(OutputFormat::Json),
+            "markdown" | "md" => Ok(OutputFormat::Markdown),
+            "csv" => Ok(OutputFormat::Csv),
+            _ => Err(anyhow!("Unknown format '{}', expected plain, json, markdown or csv", s)),
+        }
+    }
+}
+
+/// Parsed command-line options
+#[derive(Debug, Default)]
+pub struct Options {
+    /// Print the keybinds in this format instead of opening the window
+    pub list_format: Option<OutputFormat>,
+    /// Export a cheat sheet to this file instead of opening the window
+    pub cheat_sheet: Option<PathBuf>,
+    /// Config file given with `--config`, discovered when unset
+    pub config: Option<PathBuf>,
+    /// Print usage and exit
+    pub help: bool,
+}
+
+impl Options {
+    /// Parse the command-line arguments (without the program name)
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Self> {
+        let mut options = Options::default();
+        let mut args = args.into_iter();
+        
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--list" => {
+                    options.list_format.get_or_insert(OutputFormat::Plain);
+                }
+                "--format" => {
+                    let value = args.next().ok_or_else(|| anyhow!("--format needs a value"))?;
+                    options.list_format = Some(value.parse()?);
+                }
+                "--cheat-sheet" => {
+                    let value = args.next().ok_or_else(|| anyhow!("--cheat-sheet needs a file name"))?;
+                    options.cheat_sheet = Some(PathBuf::from(value));
+                }
+                "--config" => {
+                    let value = args.next().ok_or_else(|| anyhow!("--config needs a file name"))?;
+                    options.config = Some(PathBuf::from(value));
+                }
+                "-h" | "--help" => options.help = true,
+                _ => {
+                    if let Some(value) = arg.strip_prefix("--format=") {
+                        options.list_format = Some(value.parse()?);
+                    } else if let Some(value) = arg.strip_prefix("--config=") {
+                        options.config = Some(PathBuf::from(value));
+                    } else {
+                        bail!("Unknown argument '{}'\n\n{}", arg, USAGE);
+                    }
+                }
+            }
+        }
+        
+        Ok(options)
+    }
+}
+
+pub fn print_usage() {
+    println!("{}", USAGE);
+}
+
+/// Parse the config and print the keybinds to stdout without initialising GTK
+pub fn print_keybinds(format: OutputFormat, config: Option<&Path>) -> Result<()> {
+    let keybinds = effective_keybinds(config)?;
+    info!("Printing {} keybinds as {:?}", keybinds.len(), format);
+    
+    print!("{}", format_keybinds(&keybinds, format)?);
+    Ok(())
+}
+
+/// Parse the config and export a cheat sheet without initialising GTK
+pub fn export_cheat_sheet(path: &Path, config: Option<&Path>) -> Result<()> {
+    let keybinds = effective_keybinds(config)?;
+    cheatsheet::export_cheat_sheet(&keybinds, path)
+}
+
+/// Parse the config and drop the binds removed by `unbind`
+fn effective_keybinds(config: Option<&Path>) -> Result<Vec<Keybind>> {
+    let mut config_parser = ConfigParser::new(config);
+    let mut keybinds = config_parser.parse_keybinds()?;
+    keybinds.retain(|keybind| keybind.removed_by.is_none());
+    Ok(keybinds)
+}
+
+/// Render keybinds in one of the text output formats
+pub fn format_keybinds(keybinds: &[Keybind], format: OutputFormat) -> Result<String> {
+    Ok(match format {
+        OutputFormat::Plain => format_plain(keybinds),
+        OutputFormat::Json => format_json(keybinds)?,
+        OutputFormat::Markdown => format_markdown(keybinds),
+        OutputFormat::Csv => format_csv(keybinds),
+    })
+}
+
+/// One keybind per line, suitable for piping into rofi/wofi
+fn format_plain(keybinds: &[Keybind]) -> String {
+    let width = keybinds.iter().map(|k| k.combo_text().len()).max().unwrap_or(0);
+    let mut output = String::new();
+    
+    for keybind in keybinds {
+        let submap = keybind.submap.as_ref().map(|s| format!("[{}] ", s)).unwrap_or_default();
+        output.push_str(&format!("{:width$}  {}{}\n", keybind.combo_text(), submap, keybind.summary(), width = width));
+    }
+    
+    output
+}
+
+fn format_json(keybinds: &[Keybind]) -> Result<String> {
+    let entries: Vec<serde_json::Value> = keybinds
+        .iter()
+        .map(|keybind| {
+            json!({
+                "modifiers": keybind.modifiers,
+                "key": keybind.key,
+                "dispatcher": keybind.dispatcher(),
+                "args": keybind.args(),
+                "description": keybind.description,
+                "summary": keybind.summary(),
+                "category": keybind.category().name(),
+                "submap": keybind.submap,
+                "flags": keybind.flags.badges(),
+                "file": keybind.origin.as_ref().map(|o| o.file.display().to_string()),
+                "line": keybind.origin.as_ref().map(|o| o.line),
+            })
+        })
+        .collect();
+    
+    Ok(serde_json::to_string_pretty(&entries)? + "\n")
+}
+
+/// Markdown tables, one per submap
+fn format_markdown(keybinds: &[Keybind]) -> String {
+    let mut submaps: Vec<Option<&str>> = vec![None];
+    for keybind in keybinds {
+        if !submaps.contains(&keybind.submap.as_deref()) {
+            submaps.push(keybind.submap.as_deref());
+        }
+    }
+    
+    let escape = |text: &str| text.replace('|', "\\|");
+    let mut output = String::from("# Hyprland Keybinds\n");
+    
+    for submap in submaps {
+        let group: Vec<&Keybind> = keybinds.iter().filter(|k| k.submap.as_deref() == submap).collect();
+        if group.is_empty() {
+            continue;
+        }
+        
+        output.push_str(&format!("\n## {}\n\n", submap.unwrap_or("Global")));
+        output.push_str("| Keys | Action | Description |\n");
+        output.push_str("|------|--------|-------------|\n");
+        for keybind in group {
+            output.push_str(&format!(
+                "| `{}` | `{}` | {} |\n",
+                escape(&keybind.combo_text()),
+                escape(&keybind.action),
+                escape(&keybind.description.clone().or_else(|| keybind.action_description()).unwrap_or_default()),
+            ));
+        }
+    }
+    
+    output
+}
+
+fn format_csv(keybinds: &[Keybind]) -> String {
+    // Quote fields that contain separators, quotes or newlines
+    let field = |text: &str| {
+        if text.contains([',', '"', '\n']) {
+            format!("\"{}\"", text.replace('"', "\"\""))
+        } else {
+            text.to_string()
+        }
+    };
+    
+    let mut output = String::from("modifiers,key,dispatcher,args,description,submap,flags,file,line\n");
+    for keybind in keybinds {
+        let row = [
+            field(&keybind.modifiers.join(" ")),
+            field(&keybind.key),
+            field(keybind.dispatcher()),
+            field(keybind.args()),
+            field(keybind.description.as_deref().unwrap_or("")),
+            field(keybind.submap.as_deref().unwrap_or("")),
+            field(&keybind.flags.badges().join(" ")),
+            field(&keybind.origin.as_ref().map(|o| o.file.display().to_string()).unwrap_or_default()),
+            keybind.origin.as_ref().map(|o| o.line.to_string()).unwrap_or_default(),
+        ];
+        output.push_str(&row.join(","));
+        output.push('\n');
+    }
+    
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config_parser::{BindFlags, BindSource, Origin};
+    
+    fn keybind(modifiers: &[&str], key: &str, action: &str, description: Option<&str>) -> Keybind {
+        Keybind {
+            key: key.to_string(),
+            modifiers: modifiers.iter().map(|m| m.to_string()).collect(),
+            action: action.to_string(),
+            description: description.map(str::to_string),
+            flags: BindFlags::default(),
+            submap: None,
+            source: BindSource::File,
+            origin: None,
+            order: 0,
+            removed_by: None,
+        }
+    }
+    
+    /// A global bind with an origin, one with a description and a repeating bind in a submap
+    fn keybinds() -> Vec<Keybind> {
+        let mut close = keybind(&["SUPER"], "Q", "killactive", None);
+        close.origin = Some(Origin {
+            file: PathBuf::from("/home/user/.config/hypr/hyprland.conf"),
+            line: 12,
+            text: "bind = SUPER, Q, killactive".to_string(),
+        });
+        let terminal = keybind(&["SUPER", "SHIFT"], "Return", "exec, kitty", Some("Terminal, float | tile"));
+        let mut resize = keybind(&[], "L", "resizeactive, 10 0", None);
+        resize.submap = Some("resize".to_string());
+        resize.flags.repeat = true;
+        vec![close, terminal, resize]
+    }
+    
+    #[test]
+    fn plain_output() {
+        assert_eq!(
+            format_keybinds(&keybinds(), OutputFormat::Plain).unwrap(),
+            "SUPER + Q               Close window\n\
+             SUPER + SHIFT + Return  Terminal, float | tile\n\
+             L                       [resize] Resize window by 10 0\n"
+        );
+    }
+    
+    #[test]
+    fn json_output() {
+        let output = format_keybinds(&keybinds(), OutputFormat::Json).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(value, json!([
+            {
+                "modifiers": ["SUPER"],
+                "key": "Q",
+                "dispatcher": "killactive",
+                "args": "",
+                "description": null,
+                "summary": "Close window",
+                "category": "Window management",
+                "submap": null,
+                "flags": [],
+                "file": "/home/user/.config/hypr/hyprland.conf",
+                "line": 12,
+            },
+            {
+                "modifiers": ["SUPER", "SHIFT"],
+                "key": "Return",
+                "dispatcher": "exec",
+                "args": "kitty",
+                "description": "Terminal, float | tile",
+                "summary": "Terminal, float | tile",
+                "category": "Launchers",
+                "submap": null,
+                "flags": [],
+                "file": null,
+                "line": null,
+            },
+            {
+                "modifiers": [],
+                "key": "L",
+                "dispatcher": "resizeactive",
+                "args": "10 0",
+                "description": null,
+                "summary": "Resize window by 10 0",
+                "category": "Window management",
+                "submap": "resize",
+                "flags": ["repeat"],
+                "file": null,
+                "line": null,
+            },
+        ]));
+    }
+    
+    #[test]
+    fn markdown_output() {
+        assert_eq!(
+            format_keybinds(&keybinds(), OutputFormat::Markdown).unwrap(),
+            "# Hyprland Keybinds\n\
+             \n\
+             ## Global\n\
+             \n\
+             | Keys | Action | Description |\n\
+             |------|--------|-------------|\n\
+             | `SUPER + Q` | `killactive` | Close window |\n\
+             | `SUPER + SHIFT + Return` | `exec, kitty` | Terminal, float \\| tile |\n\
+             \n\
+             ## resize\n\
+             \n\
+             | Keys | Action | Description |\n\
+             |------|--------|-------------|\n\
+             | `L` | `resizeactive, 10 0` | Resize window by 10 0 |\n"
+        );
+    }
+    
+    #[test]
+    fn csv_output() {
+        assert_eq!(
+            format_keybinds(&keybinds(), OutputFormat::Csv).unwrap(),
+            "modifiers,key,dispatcher,args,description,submap,flags,file,line\n\
+             SUPER,Q,killactive,,,,,/home/user/.config/hypr/hyprland.conf,12\n\
+             SUPER SHIFT,Return,exec,kitty,\"Terminal, float | tile\",,,,\n\
+             ,L,resizeactive,10 0,,resize,repeat,,\n"
+        );
+    }
+    
+    #[test]
+    fn options_take_a_config_file() {
+        let args = |args: &[&str]| Options::parse(args.iter().map(|arg| arg.to_string()));
+        
+        let options = args(&["--config", "/tmp/hypr/test.conf", "--format=json"]).unwrap();
+        assert_eq!(options.config, Some(PathBuf::from("/tmp/hypr/test.conf")));
+        assert_eq!(options.list_format, Some(OutputFormat::Json));
+        
+        let options = args(&["--config=binds.conf", "--list"]).unwrap();
+        assert_eq!(options.config, Some(PathBuf::from("binds.conf")));
+        assert_eq!(options.list_format, Some(OutputFormat::Plain));
+        
+        assert_eq!(args(&[]).unwrap().config, None);
+        assert!(args(&["--config"]).is_err());
+        assert!(args(&["--configs=binds.conf"]).is_err());
+    }
+    
+    #[test]
+    fn listing_reads_the_given_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test.conf");
+        std::fs::write(&path, "bind = SUPER, Q, killactive\nunbind = SUPER, Q\nbind = SUPER, E, exec, nautilus\n").unwrap();
+        
+        let keybinds = effective_keybinds(Some(&path)).unwrap();
+        let keys: Vec<&str> = keybinds.iter().map(|keybind| keybind.key.as_str()).collect();
+        assert_eq!(keys, vec!["E"]);
+    }
+}
@@ -1,9 +1,150 @@
-use anyhow::{Context, Result};
-use log::{debug, info};
+use anyhow::{anyhow, Context, Result};
+use log::{debug, info, warn};
 use regex::Regex;
 use std::collections::HashMap;
 use std::fs;
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
+
+use crate::dispatchers::{self, DispatcherCategory};
+use crate::ipc;
+use crate::modifiers::modmask_from_modifiers;
+use crate::pointer::{self, Gesture};
+
+/// Flags that can be appended to the `bind` keyword, e.g. `bindel` or `bindm`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BindFlags {
+    /// `l` - works while an input inhibitor (lockscreen) is active
+    pub locked: bool,
+    /// `r` - triggers on key release
+    pub release: bool,
+    /// `c` - triggers on release if the key was not held for too long
+    pub click: bool,
+    /// `g` - triggers on release only if the key was held
+    pub drag: bool,
+    /// `o` - triggers on long press
+    pub long_press: bool,
+    /// `e` - repeats while held
+    pub repeat: bool,
+    /// `n` - the key event is passed on to the focused window
+    pub non_consuming: bool,
+    /// `m` - mouse bind
+    pub mouse: bool,
+    /// `t` - transparent, cannot be shadowed by other binds
+    pub transparent: bool,
+    /// `i` - ignores modifiers
+    pub ignore_mods: bool,
+    /// `s` - separate, allows combining keys from different keysyms
+    pub separate: bool,
+    /// `d` - bind has a description argument
+    pub has_description: bool,
+    /// `p` - bypasses the app's shortcut inhibitor
+    pub bypass_inhibit: bool,
+}
+
+impl BindFlags {
+    /// Parse the flag suffix of a bind keyword (`el` for `bindel`).
+    /// Returns `None` if the suffix contains an unknown flag.
+    pub fn from_suffix(suffix: &str) -> Option<Self> {
+        let mut flags = Self::default();
+        
+        for flag in suffix.chars() {
+            match flag {
+                'l' => flags.locked = true,
+                'r' => flags.release = true,
+                'c' => flags.click = true,
+                'g' => flags.drag = true,
+                'o' => flags.long_press = true,
+                'e' => flags.repeat = true,
+                'n' => flags.non_consuming = true,
+                'm' => flags.mouse = true,
+                't' => flags.transparent = true,
+                'i' => flags.ignore_mods = true,
+                's' => flags.separate = true,
+                'd' => flags.has_description = true,
+                'p' => flags.bypass_inhibit = true,
+                _ => return None,
+            }
+        }
+        
+        Some(flags)
+    }
+    
+    /// Short human-readable names for the flags that are set, used as UI badges
+    pub fn badges(&self) -> Vec<&'static str> {
+        let mut badges = Vec::new();
+        
+        if self.repeat { badges.push("repeat"); }
+        if self.locked { badges.push("locked"); }
+        if self.release { badges.push("release"); }
+        if self.click { badges.push("click"); }
+        if self.drag { badges.push("drag"); }
+        if self.long_press { badges.push("long press"); }
+        if self.mouse { badges.push("mouse"); }
+        if self.non_consuming { badges.push("non-consuming"); }
+        if self.transparent { badges.push("transparent"); }
+        if self.ignore_mods { badges.push("ignore mods"); }
+        if self.separate { badges.push("separate"); }
+        if self.bypass_inhibit { badges.push("bypass"); }
+        
+        badges
+    }
+}
+
+/// Where a keybind comes from when the config files are compared with the
+/// binds of the running compositor
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum BindSource {
+    /// Parsed from the config files, not compared with the compositor
+    #[default]
+    File,
+    /// Present in the config files and in the running compositor
+    Both,
+    /// Present in the config files but not loaded in the compositor
+    FileOnly,
+    /// Loaded in the compositor but not present in any config file,
+    /// e.g. added with `hyprctl keyword bind` or by a plugin
+    LiveOnly,
+}
+
+impl BindSource {
+    /// Badge text shown in the UI for binds that only exist on one side
+    pub fn badge(&self) -> Option<&'static str> {
+        match self {
+            BindSource::FileOnly => Some("file only"),
+            BindSource::LiveOnly => Some("live only"),
+            BindSource::File | BindSource::Both => None,
+        }
+    }
+}
+
+/// The place in a config file where a keybind or unbind was defined
+#[derive(Debug, Clone, PartialEq)]
+pub struct Origin {
+    pub file: PathBuf,
+    /// 1-based line number
+    pub line: usize,
+    /// The line as it was read, used to make sure edits hit the right line
+    pub text: String,
+}
+
+impl Origin {
+    /// Short `file.conf:12` text for display
+    pub fn display_short(&self) -> String {
+        let file_name = self.file
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| self.file.display().to_string());
+        format!("{}:{}", file_name, self.line)
+    }
+}
+
+/// A `source =` line that did not resolve to any file
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceWarning {
+    pub origin: Origin,
+    /// Why the source was skipped
+    pub message: String,
+}
 
 /// Represents a keybind in Hyprland config
 #[derive(Debug, Clone, PartialEq)]
@@ -12,28 +153,155 @@ pub struct Keybind {
     pub modifiers: Vec<String>,
     pub action: String,
     pub description: Option<String>,
+    pub flags: BindFlags,
+    /// The submap this bind belongs to, `None` for the global keymap
+    pub submap: Option<String>,
+    pub source: BindSource,
+    /// Where the bind was defined, `None` for binds read from the compositor
+    pub origin: Option<Origin>,
+    /// Position in the order Hyprland registers the binds, following
+    /// `source =` lines where they appear
+    pub order: usize,
+    /// The `unbind` line that removed this bind again. Removed binds are not
+    /// part of the effective keymap.
+    pub removed_by: Option<Origin>,
+}
+
+impl Keybind {
+    /// The dispatcher part of the action, e.g. `exec` for `exec, kitty`
+    pub fn dispatcher(&self) -> &str {
+        match self.action.find(',') {
+            Some(idx) => self.action[..idx].trim(),
+            None => self.action.trim(),
+        }
+    }
+    
+    /// The dispatcher arguments, e.g. `kitty` for `exec, kitty`
+    pub fn args(&self) -> &str {
+        match self.action.find(',') {
+            Some(idx) => self.action[idx + 1..].trim(),
+            None => "",
+        }
+    }
+    
+    /// The action described in plain words, if the dispatcher is known
+    pub fn action_description(&self) -> Option<String> {
+        dispatchers::describe(self.dispatcher(), self.args())
+    }
+    
+    /// Text explaining the bind: its description, the described action or the raw action
+    pub fn summary(&self) -> String {
+        self.description
+            .clone()
+            .or_else(|| self.action_description())
+            .unwrap_or_else(|| self.action.clone())
+    }
+    
+    /// What kind of action the bind performs. Mouse binds are grouped
+    /// together whatever they do.
+    pub fn category(&self) -> DispatcherCategory {
+        if self.flags.mouse || pointer::mouse_input(&self.key).is_some() {
+            return DispatcherCategory::Pointer;
+        }
+        dispatchers::category(self.dispatcher(), self.args())
+    }
+    
+    /// Format the modifiers and key, e.g. `SUPER + SHIFT + Q`
+    pub fn combo_text(&self) -> String {
+        if self.modifiers.is_empty() {
+            self.key.clone()
+        } else {
+            format!("{} + {}", self.modifiers.join(" + "), self.key)
+        }
+    }
+    
+    /// Like `combo_text`, with mouse buttons by name, e.g. `SUPER + Left button`
+    pub fn display_combo(&self) -> String {
+        let key = pointer::mouse_input(&self.key).map(|input| input.name.to_string()).unwrap_or_else(|| self.key.clone());
+        if self.modifiers.is_empty() {
+            key
+        } else {
+            format!("{} + {}", self.modifiers.join(" + "), key)
+        }
+    }
+    
+    /// Key identifying binds that trigger on the same input: submap, normalised
+    /// modifiers, key (case-insensitive) and whether it fires on release
+    pub fn combo_key(&self) -> (Option<String>, u32, String, bool) {
+        (
+            self.submap.clone(),
+            modmask_from_modifiers(&self.modifiers),
+            self.key.to_lowercase(),
+            self.flags.release,
+        )
+    }
+    
+    /// The submap this bind switches to, if it uses the `submap` dispatcher
+    pub fn entered_submap(&self) -> Option<&str> {
+        if self.dispatcher() == "submap" && !self.args().is_empty() && self.args() != "reset" {
+            Some(self.args())
+        } else {
+            None
+        }
+    }
 }
 
 /// Parser for Hyprland config files
 pub struct ConfigParser {
     config_paths: Vec<PathBuf>,
     processed_files: Vec<PathBuf>,
+    /// Glob patterns and missing files of `source =` lines, which start to
+    /// matter once a file matching them is created
+    source_patterns: Vec<PathBuf>,
+    /// Variables defined so far, by name without the `$`
     variables: HashMap<String, String>,
+    source_warnings: Vec<SourceWarning>,
+    /// Options of the `gestures` section as (name, value, origin), in config order
+    gesture_options: Vec<(String, String, Origin)>,
+    /// Gestures from `gesture =` lines
+    gesture_lines: Vec<Gesture>,
+    /// Submap that binds are currently being added to. Like in Hyprland it
+    /// carries over into sourced files.
+    current_submap: Option<String>,
 }
 
 impl ConfigParser {
-    pub fn new() -> Self {
+    /// Create a parser for `config`, or for the discovered config file when
+    /// no path was given on the command line
+    pub fn new(config: Option<&Path>) -> Self {
+        let config_paths = match config {
+            Some(path) => vec![path.to_path_buf()],
+            None => Self::find_config_paths(),
+        };
+        
         Self {
-            config_paths: Self::find_config_paths(),
+            config_paths,
             processed_files: Vec::new(),
+            source_patterns: Vec::new(),
             variables: HashMap::new(),
+            source_warnings: Vec::new(),
+            gesture_options: Vec::new(),
+            gesture_lines: Vec::new(),
+            current_submap: None,
         }
     }
-
-    /// Find possible Hyprland config file paths
+    
+    /// Find possible Hyprland config file paths, most specific first
     fn find_config_paths() -> Vec<PathBuf> {
         let mut paths = Vec::new();
         
+        // An explicitly configured file wins
+        if let Ok(config) = std::env::var("HYPRLAND_CONFIG") {
+            if !config.is_empty() {
+                paths.push(PathBuf::from(config));
+            }
+        }
+        
+        // The file the running compositor was started with
+        if let Some(config) = ipc::compositor_config_path() {
+            paths.push(config);
+        }
+        
         // Check XDG_CONFIG_HOME
         if let Ok(config_home) = std::env::var("XDG_CONFIG_HOME") {
             paths.push(PathBuf::from(config_home).join("hypr/hyprland.conf"));
@@ -43,31 +311,80 @@ impl ConfigParser {
         if let Ok(home) = std::env::var("HOME") {
             paths.push(PathBuf::from(home).join(".config/hypr/hyprland.conf"));
         }
-        
+    
     // Also check a few other places where people often store Hyprland configs.
     // If you know of more locations we should support, feel free to add them here.
         
-        debug!("Potential config paths: {:?}", paths);
-        paths
+        // XDG_CONFIG_HOME usually is ~/.config, and symlinks can point several
+        // paths at the same file
+        let mut unique: Vec<PathBuf> = Vec::new();
+        for path in paths {
+            let path = fs::canonicalize(&path).unwrap_or(path);
+            if !unique.contains(&path) {
+                unique.push(path);
+            }
+        }
+        
+        debug!("Potential config paths: {:?}", unique);
+        unique
     }
     
-    /// Parse keybinds from Hyprland config
+    /// Parse keybinds from the first config file that exists
     pub fn parse_keybinds(&mut self) -> Result<Vec<Keybind>> {
         let mut keybinds = Vec::new();
         
-        for path in self.config_paths.clone() {
-            if path.exists() {
-                info!("Found config file: {:?}", path);
-                self.process_config_file(&path, &mut keybinds)?;
-            }
-        }
+        let path = self
+            .config_paths
+            .iter()
+            .find(|path| path.is_file())
+            .cloned()
+            .ok_or_else(|| {
+                let tried: Vec<String> = self.config_paths.iter().map(|path| path.display().to_string()).collect();
+                anyhow!("No Hyprland config found, tried: {}", tried.join(", "))
+            })?;
+        
+        info!("Found config file: {:?}", path);
+        self.process_config_file(&path, &mut keybinds)?;
         
         Ok(keybinds)
     }
     
-    /// Process a config file including source statements
+    /// Config file paths that are tried, in order
+    pub fn config_paths(&self) -> &[PathBuf] {
+        &self.config_paths
+    }
+    
+    /// Config files read by the last `parse_keybinds` call
+    pub fn processed_files(&self) -> &[PathBuf] {
+        &self.processed_files
+    }
+    
+    /// Glob patterns and missing files sourced by the last `parse_keybinds` call
+    pub fn source_patterns(&self) -> &[PathBuf] {
+        &self.source_patterns
+    }
+    
+    /// Variables as defined at the end of the config, by name without the `$`
+    pub fn variables(&self) -> &HashMap<String, String> {
+        &self.variables
+    }
+    
+    /// `source =` lines that could not be resolved by the last `parse_keybinds` call
+    pub fn source_warnings(&self) -> &[SourceWarning] {
+        &self.source_warnings
+    }
+    
+    /// Touchpad gestures configured by the last `parse_keybinds` call
+    pub fn gestures(&self) -> Vec<Gesture> {
+        pointer::workspace_swipe(&self.gesture_options)
+            .into_iter()
+            .chain(self.gesture_lines.iter().cloned())
+            .collect()
+    }
+    
+    /// Process a config file, including the files it sources
     fn process_config_file(&mut self, path: &Path, keybinds: &mut Vec<Keybind>) -> Result<()> {
-        // Avoid processing the same file multiple times
+        // Avoid processing the same file multiple times, which also stops source loops
         let path_buf = path.to_path_buf();
         if self.processed_files.contains(&path_buf) {
             return Ok(());
@@ -78,96 +395,502 @@ impl ConfigParser {
         let content = fs::read_to_string(path)
             .with_context(|| format!("Failed to read config file: {:?}", path))?;
         
-        // Process source statements to include other files
-        let source_regex = Regex::new(r"(?m)^\s*source\s*=\s*(.+)$")?;
-        for cap in source_regex.captures_iter(&content) {
-            if let Some(source_path_str) = cap.get(1) {
-                let source_path_str = source_path_str.as_str().trim();
-                let source_path = if source_path_str.starts_with("~/") {
-                    if let Ok(home) = std::env::var("HOME") {
-                        PathBuf::from(home).join(&source_path_str[2..])
-                    } else {
-                        continue;
+        // Parse variables, sources and keybinds
+        self.parse_keybinds_in_content(path, &content, keybinds)?;
+        
+        Ok(())
+    }
+    
+    /// Turn the value of a `source =` line into the files it refers to.
+    /// Variables and `~` are expanded, relative paths are relative to the
+    /// sourcing file, and globs are expanded in sorted order.
+    fn resolve_source(&mut self, source: &str, current_file: &Path) -> Result<Vec<PathBuf>, String> {
+        let expanded = expand_env_vars(&self.expand_variables(source))?;
+        
+        let source_path = match expanded.strip_prefix("~/") {
+            Some(rest) => {
+                let home = std::env::var("HOME").map_err(|_| "HOME is not set".to_string())?;
+                PathBuf::from(home).join(rest)
+            }
+            None if expanded.starts_with('/') => PathBuf::from(&expanded),
+            // Relative to the current file
+            None => current_file.parent().unwrap_or(Path::new("")).join(&expanded),
+        };
+        // `./conf.d` and `conf.d` are the same files
+        let source_path: PathBuf = source_path
+            .components()
+            .filter(|component| !matches!(component, Component::CurDir))
+            .collect();
+        
+        if !expanded.contains(['*', '?', '[']) {
+            return if source_path.is_file() {
+                Ok(vec![source_path])
+            } else {
+                let message = format!("{} does not exist", source_path.display());
+                self.source_patterns.push(source_path);
+                Err(message)
+            };
+        }
+        self.source_patterns.push(source_path.clone());
+        
+        let pattern = source_path.to_string_lossy();
+        let mut matches: Vec<PathBuf> = glob::glob(&pattern)
+            .map_err(|e| format!("Invalid pattern {}: {}", pattern, e))?
+            .filter_map(|entry| entry.ok())
+            .filter(|path| path.is_file())
+            .collect();
+        matches.sort();
+        
+        if matches.is_empty() {
+            return Err(format!("{} matches no files", pattern));
+        }
+        Ok(matches)
+    }
+    
+    /// Parse keybinds from file content line by line, the way Hyprland reads
+    /// it. A `source =` line is processed where it appears, so the sourced
+    /// file sees the variables and submap defined above it, and its binds
+    /// come before the binds below it.
+    fn parse_keybinds_in_content(&mut self, path: &Path, content: &str, keybinds: &mut Vec<Keybind>) -> Result<()> {
+        // Match every bind keyword variant - Hyprland format: bind[flags] = MOD, KEY, DISPATCHER, ARGS
+        let bind_regex = Regex::new(r"^\s*bind([a-z]*)\s*=\s*(.+)$")?;
+        let desc_regex = Regex::new(r"^\s*#\s*desc:\s*(.+)$")?;
+        let submap_regex = Regex::new(r"^\s*submap\s*=\s*(\S+)\s*$")?;
+        let unbind_regex = Regex::new(r"^\s*unbind\s*=\s*([^,]*),\s*(.+?)\s*$")?;
+        let var_regex = Regex::new(r"^\s*\$(\w+)\s*=\s*(.*?)\s*$")?;
+        let source_regex = Regex::new(r"^\s*source\s*=\s*(.+)$")?;
+        let option_regex = Regex::new(r"^\s*([\w:.-]+)\s*=\s*(.*?)\s*$")?;
+        
+        // Description from a `# desc:` comment on the line above a bind
+        let mut pending_description: Option<String> = None;
+        // Names of the `name { }` sections the current line is in
+        let mut sections: Vec<String> = Vec::new();
+        
+        for (line_idx, line) in content.lines().enumerate() {
+            let origin = Origin {
+                file: path.to_path_buf(),
+                line: line_idx + 1,
+                text: line.to_string(),
+            };
+            
+            if let Some(cap) = desc_regex.captures(line) {
+                pending_description = Some(cap[1].trim().to_string());
+                continue;
+            }
+            
+            let comment_description = pending_description.take();
+            let (code, trailing_comment) = Self::split_comment(line);
+            
+            // Variables only apply to the lines after their definition, and a
+            // value can use the variables defined before it
+            if let Some(cap) = var_regex.captures(&code) {
+                let value = self.expand_variables(&cap[2]);
+                debug!("Found variable: ${} = {}", &cap[1], value);
+                self.variables.insert(cap[1].to_string(), value);
+                continue;
+            }
+            
+            // Sourced files are parsed right away, variables in the path are
+            // expanded while resolving it
+            if let Some(cap) = source_regex.captures(&code) {
+                match self.resolve_source(cap[1].trim(), path) {
+                    Ok(source_paths) => {
+                        for source_path in source_paths {
+                            info!("Processing sourced file: {:?}", source_path);
+                            self.process_config_file(&source_path, keybinds)?;
+                        }
                     }
-                } else if source_path_str.starts_with("/") {
-                    PathBuf::from(source_path_str)
-                } else {
-                    // Relative to the current file
-                    let parent = path.parent().unwrap_or(Path::new(""));
-                    parent.join(source_path_str)
+                    Err(message) => {
+                        warn!("Skipping source at {}: {}", origin.display_short(), message);
+                        self.source_warnings.push(SourceWarning { origin, message });
+                    }
+                }
+                continue;
+            }
+            let code = self.expand_variables(&code);
+            
+            if let Some(cap) = submap_regex.captures(&code) {
+                self.current_submap = match &cap[1] {
+                    "reset" => None,
+                    name => Some(name.to_string()),
                 };
-                
-                info!("Processing sourced file: {:?}", source_path);
-                if source_path.exists() {
-                    self.process_config_file(&source_path, keybinds)?;
+                debug!("Entering submap: {:?}", self.current_submap);
+                continue;
+            }
+            
+            // Track sections so `gestures { workspace_swipe = true }` and
+            // `gestures:workspace_swipe = true` end up the same
+            if let Some(name) = code.trim().strip_suffix('{') {
+                sections.push(name.trim().to_string());
+                continue;
+            }
+            if code.trim() == "}" {
+                sections.pop();
+                continue;
+            }
+            if let Some(cap) = option_regex.captures(&code) {
+                let name = sections.iter().map(String::as_str).chain([&cap[1]]).collect::<Vec<_>>().join(":");
+                if let Some(option) = name.strip_prefix("gestures:") {
+                    self.gesture_options.push((option.to_string(), cap[2].to_string(), origin));
+                    continue;
+                }
+                if name == "gesture" {
+                    match pointer::parse_gesture_line(&cap[2], origin) {
+                        Some(gesture) => self.gesture_lines.push(gesture),
+                        None => debug!("Skipping malformed gesture: {}", line),
+                    }
+                    continue;
                 }
             }
-        }
-        
-        // Parse variable definitions
-        let var_regex = Regex::new(r"(?m)^\s*\$(\w+)\s*=\s*(.+)$")?;
-        for cap in var_regex.captures_iter(&content) {
-            if cap.len() >= 3 {
-                let var_name = cap[1].trim();
-                let var_value = cap[2].trim();
-                self.variables.insert(format!("${}", var_name), var_value.to_string());
-                debug!("Found variable: ${} = {}", var_name, var_value);
+            
+            // An unbind removes the matching binds registered before it,
+            // binds after it are not affected
+            if let Some(cap) = unbind_regex.captures(&code) {
+                debug!("Found unbind: '{}', key: '{}'", &cap[1], &cap[2]);
+                let modifiers: Vec<String> = cap[1].split_whitespace().map(str::to_string).collect();
+                let modmask = modmask_from_modifiers(&modifiers);
+                for keybind in keybinds.iter_mut().filter(|keybind| keybind.removed_by.is_none()) {
+                    if modmask_from_modifiers(&keybind.modifiers) == modmask && keybind.key.eq_ignore_ascii_case(&cap[2]) {
+                        keybind.removed_by = Some(origin.clone());
+                    }
+                }
+                continue;
+            }
+            
+            let cap = match bind_regex.captures(&code) {
+                Some(cap) => cap,
+                None => continue,
+            };
+            
+            let suffix = &cap[1];
+            let flags = match BindFlags::from_suffix(suffix) {
+                Some(flags) => flags,
+                None => {
+                    debug!("Skipping unknown bind keyword: bind{}", suffix);
+                    continue;
+                }
+            };
+            
+            // bindd takes an extra description argument before the dispatcher
+            let field_count = if flags.has_description { 5 } else { 4 };
+            let fields: Vec<&str> = cap[2].splitn(field_count, ',').map(str::trim).collect();
+            if fields.len() < 2 {
+                debug!("Skipping malformed keybind: {}", line);
+                continue;
             }
+            
+            let mod_combo = fields[0];
+            let key = fields[1];
+            let (bind_description, action_fields) = if flags.has_description {
+                (fields.get(2).copied(), fields.get(3..).unwrap_or(&[]))
+            } else {
+                (None, &fields[2..])
+            };
+            let action = match action_fields {
+                [dispatcher, args] if !args.is_empty() => format!("{}, {}", dispatcher, args),
+                [dispatcher, ..] => dispatcher.to_string(),
+                [] => String::new(),
+            };
+            
+            // Prefer the bindd argument, then a trailing comment, then a `# desc:` line
+            let description = bind_description
+                .filter(|d| !d.is_empty())
+                .map(str::to_string)
+                .or(trailing_comment)
+                .or(comment_description);
+            
+            debug!("Processing keybind{}: '{}', key: '{}', action: '{}'", 
+                   suffix, mod_combo, key, action);
+            
+            // Parse modifiers
+            let modifiers: Vec<String> = if mod_combo.is_empty() {
+                Vec::new()
+            } else {
+                mod_combo.split_whitespace()
+                   .map(|s| s.to_string())
+                   .collect()
+            };
+            
+            debug!("  Parsed as key: '{}', modifiers: '{:?}', flags: {:?}", key, modifiers, flags);
+            
+            keybinds.push(Keybind {
+                key: key.to_string(),
+                modifiers,
+                action,
+                description,
+                flags,
+                submap: self.current_submap.clone(),
+                source: BindSource::File,
+                origin: Some(origin),
+                order: keybinds.len(),
+                removed_by: None,
+            });
         }
         
-        // Parse keybinds
-        self.parse_keybinds_in_content(&content, keybinds)?;
-        
         Ok(())
     }
     
-    /// Parse keybinds from file content
-    fn parse_keybinds_in_content(&self, content: &str, keybinds: &mut Vec<Keybind>) -> Result<()> {
-        // Parse keyboard bindings with regex - Hyprland format: bind = MOD, KEY, ACTION
-        let bind_regex = Regex::new(r"(?m)^\s*bind\s*=\s*([^,]+),\s*([^,]+),?\s*(.*)$")?;
+    /// Split a config line into its code and trailing comment.
+    /// Hyprland treats `##` as an escaped literal `#`.
+    pub fn split_comment(line: &str) -> (String, Option<String>) {
+        let mut code = String::with_capacity(line.len());
+        let mut chars = line.char_indices().peekable();
         
-        for cap in bind_regex.captures_iter(content) {
-            if cap.len() >= 3 {
-                let mod_combo = cap[1].trim();
-                let key = cap[2].trim();
-                let action = if cap.len() >= 4 { cap[3].trim() } else { "" };
-                
-                // Expand variables in the mod combo
-                let expanded_mods = self.expand_variables(mod_combo);
-                debug!("Processing keybind: '{}' -> '{}', key: '{}', action: '{}'", 
-                       mod_combo, expanded_mods, key, action);
-                
-                // Parse modifiers
-                let modifiers: Vec<String> = if expanded_mods.is_empty() {
-                    Vec::new()
-                } else {
-                    expanded_mods.split_whitespace()
-                       .map(|s| s.to_string())
-                       .collect()
-                };
-                
-                debug!("  Parsed as key: '{}', modifiers: '{:?}'", key, modifiers);
+        while let Some((idx, c)) = chars.next() {
+            if c == '#' {
+                if let Some((_, '#')) = chars.peek() {
+                    chars.next();
+                    code.push('#');
+                    continue;
+                }
                 
-                keybinds.push(Keybind {
-                    key: key.to_string(),
-                    modifiers,
-                    action: action.to_string(),
-                    description: None,
-                });
+                let comment = line[idx + 1..].trim();
+                let comment = if comment.is_empty() { None } else { Some(comment.to_string()) };
+                return (code, comment);
             }
+            code.push(c);
         }
         
-        Ok(())
+        (code, None)
     }
     
-    /// Expand variables in a string
+    /// Expand `$name` references with the variables defined so far. Like
+    /// Hyprland, the longest defined name wins, so `$mainMod` does not clobber
+    /// `$mainModShift`. Unknown names are left as they are.
     fn expand_variables(&self, input: &str) -> String {
-        let mut result = input.to_string();
+        let mut result = String::with_capacity(input.len());
+        let mut rest = input;
         
-        for (var_name, var_value) in &self.variables {
-            result = result.replace(var_name, var_value);
+        while let Some(idx) = rest.find('$') {
+            result.push_str(&rest[..idx]);
+            let after = &rest[idx + 1..];
+            
+            // The longest run of name characters after the `$`
+            let name_len = after
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(after.len());
+            let name = &after[..name_len];
+            
+            // Values were expanded when they were defined, so nested variables need no second pass
+            let matched = (1..=name.len())
+                .rev()
+                .find_map(|len| self.variables.get(&name[..len]).map(|value| (len, value)));
+            match matched {
+                Some((len, value)) => {
+                    result.push_str(value);
+                    rest = &after[len..];
+                }
+                None => {
+                    result.push('$');
+                    rest = after;
+                }
+            }
         }
         
+        result.push_str(rest);
         result
     }
-} 
\ No newline at end of file
+} 
+
+/// Expand `$NAME` and `${NAME}` environment variables in a path. Fails on
+/// variables that are not set, the path would point somewhere else.
+fn expand_env_vars(input: &str) -> Result<String, String> {
+    let env_regex = Regex::new(r"\$(?:\{(\w+)\}|(\w+))").map_err(|e| e.to_string())?;
+    let mut missing = Vec::new();
+    
+    let expanded = env_regex.replace_all(input, |cap: &regex::Captures| {
+        let name = cap.get(1).or_else(|| cap.get(2)).map(|m| m.as_str()).unwrap_or_default();
+        std::env::var(name).unwrap_or_else(|_| {
+            missing.push(format!("${}", name));
+            String::new()
+        })
+    });
+    
+    if missing.is_empty() {
+        Ok(expanded.to_string())
+    } else {
+        Err(format!("{} is not set", missing.join(", ")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    /// Parse `config` written to a file in a temporary directory
+    fn parse(config: &str) -> (ConfigParser, Vec<Keybind>) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("hyprland.conf");
+        fs::write(&path, config).unwrap();
+        
+        let mut parser = ConfigParser::new(Some(&path));
+        let keybinds = parser.parse_keybinds().unwrap();
+        (parser, keybinds)
+    }
+    
+    fn parser_with(variables: &[(&str, &str)]) -> ConfigParser {
+        let mut parser = ConfigParser::new(Some(Path::new("hyprland.conf")));
+        for (name, value) in variables {
+            parser.variables.insert(name.to_string(), value.to_string());
+        }
+        parser
+    }
+    
+    #[test]
+    fn longest_variable_name_wins() {
+        let parser = parser_with(&[("mainMod", "SUPER"), ("mainModShift", "SUPER SHIFT")]);
+        assert_eq!(parser.expand_variables("$mainMod, Q"), "SUPER, Q");
+        assert_eq!(parser.expand_variables("$mainModShift, Q"), "SUPER SHIFT, Q");
+        // Only a prefix is defined, the rest of the name stays
+        assert_eq!(parser.expand_variables("$mainModAlt"), "SUPERAlt");
+    }
+    
+    #[test]
+    fn unknown_variables_are_kept() {
+        let parser = parser_with(&[("terminal", "kitty")]);
+        assert_eq!(parser.expand_variables("exec, $browser"), "exec, $browser");
+        assert_eq!(parser.expand_variables("$ $terminal $"), "$ kitty $");
+    }
+    
+    #[test]
+    fn nested_variables_are_expanded() {
+        let (parser, keybinds) = parse(
+            "$terminal = kitty\n\
+             $scratchpad = $terminal --class scratchpad\n\
+             bind = SUPER, S, exec, $scratchpad\n",
+        );
+        assert_eq!(parser.variables()["scratchpad"], "kitty --class scratchpad");
+        assert_eq!(keybinds[0].action, "exec, kitty --class scratchpad");
+    }
+    
+    #[test]
+    fn variables_apply_from_their_definition_on() {
+        let (_, keybinds) = parse(
+            "bind = SUPER, Return, exec, $terminal\n\
+             $terminal = kitty\n\
+             bind = SUPER, T, exec, $terminal\n\
+             $terminal = foot\n\
+             bind = SUPER SHIFT, T, exec, $terminal\n",
+        );
+        let actions: Vec<&str> = keybinds.iter().map(|keybind| keybind.action.as_str()).collect();
+        assert_eq!(actions, vec!["exec, $terminal", "exec, kitty", "exec, foot"]);
+    }
+    
+    #[test]
+    fn variables_expand_in_modifiers_keys_and_actions() {
+        let (_, keybinds) = parse(
+            "$mainMod = SUPER\n\
+             $mainModShift = SUPER SHIFT\n\
+             $left = H\n\
+             $move = movefocus\n\
+             bind = $mainMod, $left, $move, l\n\
+             bind = $mainModShift, $left, movewindow, l\n",
+        );
+        assert_eq!(keybinds[0].modifiers, vec!["SUPER"]);
+        assert_eq!(keybinds[0].key, "H");
+        assert_eq!(keybinds[0].action, "movefocus, l");
+        assert_eq!(keybinds[1].modifiers, vec!["SUPER", "SHIFT"]);
+        assert_eq!(keybinds[1].key, "H");
+    }
+    
+    #[test]
+    fn glob_and_missing_sources_are_recorded() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("conf.d")).unwrap();
+        fs::write(dir.path().join("conf.d").join("apps.conf"), "bind = SUPER, B, exec, firefox\n").unwrap();
+        fs::write(dir.path().join("binds.conf"), "bind = SUPER, Q, killactive\n").unwrap();
+        let path = dir.path().join("hyprland.conf");
+        fs::write(&path, "source = ./binds.conf\nsource = conf.d/*.conf\nsource = local.conf\n").unwrap();
+        
+        let mut parser = ConfigParser::new(Some(&path));
+        assert_eq!(parser.parse_keybinds().unwrap().len(), 2);
+        assert_eq!(parser.source_patterns(), &[
+            dir.path().join("conf.d/*.conf"),
+            dir.path().join("local.conf"),
+        ]);
+    }
+    
+    #[test]
+    fn bind_flags_come_from_the_suffix() {
+        let flags = BindFlags::from_suffix("el").unwrap();
+        assert!(flags.repeat && flags.locked);
+        assert_eq!(flags.badges(), vec!["repeat", "locked"]);
+        
+        let flags = BindFlags::from_suffix("rl").unwrap();
+        assert!(flags.release && flags.locked && !flags.repeat);
+        
+        assert_eq!(BindFlags::from_suffix(""), Some(BindFlags::default()));
+        assert_eq!(BindFlags::from_suffix("x"), None);
+        
+        let (_, keybinds) = parse(
+            "bindel = , XF86AudioRaiseVolume, exec, wpctl set-volume @DEFAULT_AUDIO_SINK@ 5%+\n\
+             bindx = SUPER, X, exec, nothing\n",
+        );
+        assert_eq!(keybinds.len(), 1);
+        assert!(keybinds[0].flags.repeat && keybinds[0].flags.locked);
+        assert!(keybinds[0].modifiers.is_empty());
+    }
+    
+    #[test]
+    fn bindd_descriptions_come_before_the_dispatcher() {
+        let (_, keybinds) = parse(
+            "bindd = SUPER, Q, Close window, killactive\n\
+             bindd = SUPER, Return, Open terminal, exec, kitty --single-instance\n\
+             bindd = SUPER, E, , exec, nautilus # Files\n",
+        );
+        assert_eq!(keybinds[0].description.as_deref(), Some("Close window"));
+        assert_eq!(keybinds[0].action, "killactive");
+        assert_eq!(keybinds[1].description.as_deref(), Some("Open terminal"));
+        assert_eq!(keybinds[1].action, "exec, kitty --single-instance");
+        // An empty description falls back to the trailing comment
+        assert_eq!(keybinds[2].description.as_deref(), Some("Files"));
+        assert_eq!(keybinds[2].action, "exec, nautilus");
+    }
+    
+    #[test]
+    fn binds_belong_to_the_submap_they_are_in() {
+        let (_, keybinds) = parse(
+            "bind = SUPER, R, submap, resize\n\
+             submap = resize\n\
+             binde = , L, resizeactive, 10 0\n\
+             bind = , Escape, submap, reset\n\
+             submap = reset\n\
+             bind = SUPER, Q, killactive\n",
+        );
+        let submaps: Vec<Option<&str>> = keybinds.iter().map(|keybind| keybind.submap.as_deref()).collect();
+        assert_eq!(submaps, vec![None, Some("resize"), Some("resize"), None]);
+        
+        assert_eq!(keybinds[0].entered_submap(), Some("resize"));
+        assert_eq!(keybinds[2].entered_submap(), None);
+        assert_eq!(keybinds[3].entered_submap(), None);
+    }
+    
+    #[test]
+    fn binds_keep_the_order_of_the_files() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("apps.conf"), "bind = SUPER, B, exec, firefox\nbind = SUPER, E, exec, nautilus\n").unwrap();
+        let path = dir.path().join("hyprland.conf");
+        fs::write(&path, "bind = SUPER, Q, killactive\nsource = apps.conf\nbind = SUPER, F, fullscreen\n").unwrap();
+        
+        let mut parser = ConfigParser::new(Some(&path));
+        let keybinds = parser.parse_keybinds().unwrap();
+        let keys: Vec<(&str, usize)> = keybinds.iter().map(|keybind| (keybind.key.as_str(), keybind.order)).collect();
+        assert_eq!(keys, vec![("Q", 0), ("B", 1), ("E", 2), ("F", 3)]);
+        assert_eq!(keybinds[1].origin.as_ref().unwrap().file, dir.path().join("apps.conf"));
+        assert_eq!(keybinds[1].origin.as_ref().unwrap().line, 1);
+    }
+    
+    #[test]
+    fn unbind_removes_only_earlier_binds() {
+        let (_, keybinds) = parse(
+            "bind = SUPER, Q, killactive\n\
+             bind = SUPER SHIFT, Q, exit\n\
+             unbind = MOD4, q\n\
+             bind = SUPER, Q, exec, kitty\n",
+        );
+        assert_eq!(keybinds.len(), 3);
+        assert_eq!(keybinds[0].removed_by.as_ref().map(|origin| origin.line), Some(3));
+        assert_eq!(keybinds[1].removed_by, None);
+        assert_eq!(keybinds[2].removed_by, None);
+    }
+}
@@ -0,0 +1,213 @@
+use log::debug;
+use std::collections::HashMap;
+
+use crate::config_parser::{Keybind, Origin};
+
+/// The kind of problem found between keybinds
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConflictKind {
+    /// The same combo is bound to the same action more than once
+    Duplicate,
+    /// A later definition binds the combo to a different action
+    Shadowed,
+    /// An `unbind` statement removes the bind
+    Unbound,
+}
+
+/// A conflict between keybinds that trigger on the same combo
+#[derive(Debug, Clone)]
+pub struct Conflict {
+    pub kind: ConflictKind,
+    /// Indices into the keybind list, in definition order
+    pub keybinds: Vec<usize>,
+    /// The `unbind` statement for `ConflictKind::Unbound`
+    pub unbind: Option<Origin>,
+}
+
+impl Conflict {
+    /// Human-readable description of the conflict
+    pub fn message(&self, keybinds: &[Keybind]) -> String {
+        let combo = self.keybinds
+            .first()
+            .map(|&idx| keybinds[idx].combo_text())
+            .unwrap_or_default();
+        let locations: Vec<String> = self.keybinds
+            .iter()
+            .map(|&idx| location_text(&keybinds[idx]))
+            .collect();
+        
+        match self.kind {
+            ConflictKind::Duplicate => format!(
+                "{} is bound to the same action {} times ({})",
+                combo, self.keybinds.len(), locations.join(", ")
+            ),
+            ConflictKind::Shadowed => format!(
+                "{} is bound to different actions ({}), the last definition wins",
+                combo, locations.join(", ")
+            ),
+            ConflictKind::Unbound => format!(
+                "{} ({}) is removed by unbind at {}",
+                combo,
+                locations.join(", "),
+                self.unbind.as_ref().map(Origin::display_short).unwrap_or_default()
+            ),
+        }
+    }
+}
+
+fn location_text(keybind: &Keybind) -> String {
+    keybind.origin
+        .as_ref()
+        .map(Origin::display_short)
+        .unwrap_or_else(|| "live".to_string())
+}
+
+/// Find duplicated, shadowed and unbound keybinds
+pub fn detect_conflicts(keybinds: &[Keybind]) -> Vec<Conflict> {
+    let mut conflicts = Vec::new();
+    
+    // Group the binds of the effective keymap by the combo they trigger on,
+    // keeping definition order
+    let mut in_order: Vec<(usize, &Keybind)> = keybinds
+        .iter()
+        .enumerate()
+        .filter(|(_, keybind)| keybind.removed_by.is_none())
+        .collect();
+    in_order.sort_by_key(|(_, keybind)| keybind.order);
+    
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    let mut group_lookup = HashMap::new();
+    for (idx, keybind) in in_order {
+        let group = *group_lookup.entry(keybind.combo_key()).or_insert_with(|| {
+            groups.push(Vec::new());
+            groups.len() - 1
+        });
+        groups[group].push(idx);
+    }
+    
+    for group in groups.iter().filter(|group| group.len() > 1) {
+        // Split the group into binds with identical actions and binds with different ones
+        let mut by_action: Vec<Vec<usize>> = Vec::new();
+        for &idx in group {
+            match by_action.iter_mut().find(|same| keybinds[same[0]].action == keybinds[idx].action) {
+                Some(same) => same.push(idx),
+                None => by_action.push(vec![idx]),
+            }
+        }
+        
+        for same in by_action.iter().filter(|same| same.len() > 1) {
+            conflicts.push(Conflict {
+                kind: ConflictKind::Duplicate,
+                keybinds: same.clone(),
+                unbind: None,
+            });
+        }
+        
+        if by_action.len() > 1 {
+            conflicts.push(Conflict {
+                kind: ConflictKind::Shadowed,
+                keybinds: group.clone(),
+                unbind: None,
+            });
+        }
+    }
+    
+    // Binds removed by the same unbind are reported together
+    let mut removed: Vec<(&Origin, Vec<usize>)> = Vec::new();
+    for (idx, keybind) in keybinds.iter().enumerate() {
+        let Some(unbind) = &keybind.removed_by else {
+            continue;
+        };
+        match removed.iter_mut().find(|(origin, _)| *origin == unbind) {
+            Some((_, indices)) => indices.push(idx),
+            None => removed.push((unbind, vec![idx])),
+        }
+    }
+    for (unbind, indices) in removed {
+        conflicts.push(Conflict {
+            kind: ConflictKind::Unbound,
+            keybinds: indices,
+            unbind: Some(unbind.clone()),
+        });
+    }
+    
+    debug!("Detected {} keybind conflicts", conflicts.len());
+    conflicts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config_parser::{BindFlags, BindSource};
+    
+    fn keybind(order: usize, modifiers: &str, key: &str, action: &str, submap: Option<&str>) -> Keybind {
+        Keybind {
+            key: key.to_string(),
+            modifiers: modifiers.split_whitespace().map(str::to_string).collect(),
+            action: action.to_string(),
+            description: None,
+            flags: BindFlags::default(),
+            submap: submap.map(str::to_string),
+            source: BindSource::File,
+            origin: None,
+            order,
+            removed_by: None,
+        }
+    }
+    
+    fn kinds(conflicts: &[Conflict]) -> Vec<(ConflictKind, Vec<usize>)> {
+        conflicts.iter().map(|conflict| (conflict.kind, conflict.keybinds.clone())).collect()
+    }
+    
+    #[test]
+    fn same_combo_in_one_submap_conflicts() {
+        let keybinds = [
+            keybind(0, "SUPER", "Q", "killactive", None),
+            keybind(1, "SUPER", "q", "killactive", None),
+            keybind(2, "SUPER", "Q", "exec, kitty", None),
+        ];
+        assert_eq!(kinds(&detect_conflicts(&keybinds)), vec![
+            (ConflictKind::Duplicate, vec![0, 1]),
+            (ConflictKind::Shadowed, vec![0, 1, 2]),
+        ]);
+    }
+    
+    #[test]
+    fn same_combo_in_different_submaps_does_not_conflict() {
+        let keybinds = [
+            keybind(0, "", "Escape", "submap, reset", Some("resize")),
+            keybind(1, "", "Escape", "submap, reset", Some("move")),
+            keybind(2, "", "Escape", "exec, notify-send", None),
+        ];
+        assert!(detect_conflicts(&keybinds).is_empty());
+    }
+    
+    #[test]
+    fn modifier_aliases_trigger_on_the_same_combo() {
+        let keybinds = [
+            keybind(0, "SUPER ALT", "Q", "killactive", None),
+            keybind(1, "MOD4 MOD1", "Q", "exec, kitty", None),
+        ];
+        let conflicts = detect_conflicts(&keybinds);
+        assert_eq!(kinds(&conflicts), vec![(ConflictKind::Shadowed, vec![0, 1])]);
+        assert_eq!(
+            conflicts[0].message(&keybinds),
+            "SUPER + ALT + Q is bound to different actions (live, live), the last definition wins"
+        );
+    }
+    
+    #[test]
+    fn binds_removed_by_one_unbind_are_reported_together() {
+        let unbind = Origin { file: "hyprland.conf".into(), line: 3, text: "unbind = SUPER, Q".to_string() };
+        let mut keybinds = [
+            keybind(0, "SUPER", "Q", "killactive", None),
+            keybind(1, "SUPER", "Q", "exec, kitty", None),
+        ];
+        for keybind in &mut keybinds {
+            keybind.removed_by = Some(unbind.clone());
+        }
+        let conflicts = detect_conflicts(&keybinds);
+        assert_eq!(kinds(&conflicts), vec![(ConflictKind::Unbound, vec![0, 1])]);
+        assert_eq!(conflicts[0].unbind, Some(unbind));
+    }
+}
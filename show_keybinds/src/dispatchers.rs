@@ -0,0 +1,233 @@
+//! Knowledge about Hyprland dispatchers
+
+/// Rough grouping of what a bind does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DispatcherCategory {
+    Workspaces,
+    Windows,
+    Launchers,
+    Media,
+    System,
+    /// Mouse buttons, scrolling and touchpad gestures
+    Pointer,
+}
+
+impl DispatcherCategory {
+    /// All categories in display order
+    pub const ALL: [DispatcherCategory; 6] = [
+        DispatcherCategory::Workspaces,
+        DispatcherCategory::Windows,
+        DispatcherCategory::Launchers,
+        DispatcherCategory::Media,
+        DispatcherCategory::System,
+        DispatcherCategory::Pointer,
+    ];
+    
+    pub fn name(&self) -> &'static str {
+        match self {
+            DispatcherCategory::Workspaces => "Workspaces",
+            DispatcherCategory::Windows => "Window management",
+            DispatcherCategory::Launchers => "Launchers",
+            DispatcherCategory::Media => "Media",
+            DispatcherCategory::System => "System",
+            DispatcherCategory::Pointer => "Mouse & gestures",
+        }
+    }
+    
+    /// Suffix for CSS classes, e.g. `keyboard-key-windows`
+    pub fn css_name(&self) -> &'static str {
+        match self {
+            DispatcherCategory::Workspaces => "workspaces",
+            DispatcherCategory::Windows => "windows",
+            DispatcherCategory::Launchers => "launchers",
+            DispatcherCategory::Media => "media",
+            DispatcherCategory::System => "system",
+            DispatcherCategory::Pointer => "pointer",
+        }
+    }
+}
+
+/// Programs that control audio, players or the backlight
+const MEDIA_COMMANDS: &[&str] = &[
+    "playerctl", "wpctl", "pactl", "pamixer", "amixer", "pulsemixer", "brightnessctl", "light", "mpc", "swayosd-client",
+];
+
+/// Find the category of a dispatcher. `exec` binds are launchers unless
+/// they run a known media command.
+pub fn category(dispatcher: &str, args: &str) -> DispatcherCategory {
+    match dispatcher.to_lowercase().as_str() {
+        "exec" | "execr" => {
+            if program_name(args).is_some_and(|program| MEDIA_COMMANDS.contains(&program)) {
+                DispatcherCategory::Media
+            } else {
+                DispatcherCategory::Launchers
+            }
+        }
+        "workspace" | "movetoworkspace" | "movetoworkspacesilent" | "togglespecialworkspace"
+        | "focusworkspaceoncurrentmonitor" | "movecurrentworkspacetomonitor" | "moveworkspacetomonitor"
+        | "swapactiveworkspaces" | "renameworkspace" | "focusmonitor" => DispatcherCategory::Workspaces,
+        "killactive" | "forcekillactive" | "closewindow" | "killwindow" | "movefocus" | "movewindow"
+        | "swapwindow" | "resizeactive" | "moveactive" | "resizewindowpixel" | "movewindowpixel"
+        | "togglefloating" | "setfloating" | "settiled" | "fullscreen" | "fullscreenstate" | "fakefullscreen"
+        | "pseudo" | "pin" | "centerwindow" | "togglesplit" | "swapsplit" | "splitratio" | "cyclenext"
+        | "swapnext" | "focuswindow" | "focusurgentorlast" | "focuscurrentorlast" | "bringactivetotop"
+        | "alterzorder" | "togglegroup" | "changegroupactive" | "moveintogroup" | "moveoutofgroup"
+        | "movewindoworgroup" | "movegroupwindow" | "lockgroups" | "lockactivegroup" | "denywindowfromgroup"
+        | "setignoregrouplock" | "resizewindow" | "layoutmsg" | "tagwindow" | "setprop" | "toggleopaque" => DispatcherCategory::Windows,
+        _ => DispatcherCategory::System,
+    }
+}
+
+/// Human-readable name of a direction argument (`l`, `r`, `u`, `d`)
+fn direction(arg: &str) -> Option<&'static str> {
+    match arg.trim().to_lowercase().as_str() {
+        "l" | "left" => Some("left"),
+        "r" | "right" => Some("right"),
+        "u" | "t" | "up" | "top" => Some("up"),
+        "d" | "b" | "down" | "bottom" => Some("down"),
+        _ => None,
+    }
+}
+
+/// Human-readable name of a workspace argument, e.g. `3`, `e+1` or `special:scratch`
+fn workspace_name(arg: &str) -> String {
+    let arg = arg.trim();
+    match arg {
+        "e+1" | "m+1" | "r+1" | "+1" => "the next workspace".to_string(),
+        "e-1" | "m-1" | "r-1" | "-1" => "the previous workspace".to_string(),
+        "previous" | "previous_per_monitor" => "the previous workspace".to_string(),
+        "empty" | "emptym" | "emptynm" => "an empty workspace".to_string(),
+        "special" => "the special workspace".to_string(),
+        _ => match arg.strip_prefix("special:").or_else(|| arg.strip_prefix("name:")) {
+            Some(name) => format!("workspace {}", name),
+            None => format!("workspace {}", arg),
+        },
+    }
+}
+
+/// The program started by an `exec` argument, skipping window rules like `[float]`
+fn program_name(args: &str) -> Option<&str> {
+    let args = match args.trim_start().strip_prefix('[') {
+        Some(rest) => rest.split_once(']').map(|(_, command)| command).unwrap_or(""),
+        None => args,
+    };
+    
+    args.split_whitespace()
+        .find(|word| !word.contains('='))
+        .map(|word| word.rsplit('/').next().unwrap_or(word))
+}
+
+/// Describe what a dispatcher does in plain words, e.g. `Move window to
+/// workspace 3 (silent)` for `movetoworkspacesilent, 3`. Returns `None` for
+/// dispatchers that are not known.
+pub fn describe(dispatcher: &str, args: &str) -> Option<String> {
+    let args = args.trim();
+    // Workspace dispatchers may carry a window after a comma
+    let first_arg = args.split(',').next().unwrap_or("").trim();
+    
+    let text = match dispatcher.to_lowercase().as_str() {
+        "exec" | "execr" => format!("Run {}", program_name(args)?),
+        "killactive" => "Close window".to_string(),
+        "forcekillactive" => "Force close window".to_string(),
+        "closewindow" => format!("Close {}", args),
+        "workspace" => format!("Switch to {}", workspace_name(first_arg)),
+        "movetoworkspace" => format!("Move window to {}", workspace_name(first_arg)),
+        "movetoworkspacesilent" => format!("Move window to {} (silent)", workspace_name(first_arg)),
+        "togglespecialworkspace" if args.is_empty() => "Toggle the special workspace".to_string(),
+        "togglespecialworkspace" => format!("Toggle special workspace {}", args),
+        "focusworkspaceoncurrentmonitor" => format!("Show {} on this monitor", workspace_name(first_arg)),
+        "movecurrentworkspacetomonitor" => format!("Move workspace to monitor {}", args),
+        "focusmonitor" => format!("Focus monitor {}", args),
+        "swapactiveworkspaces" => "Swap workspaces between monitors".to_string(),
+        "movefocus" => format!("Focus window {}", direction(args)?),
+        // bindm uses movewindow and resizewindow without arguments
+        "movewindow" if args.is_empty() => "Move window with the mouse".to_string(),
+        "resizewindow" => "Resize window with the mouse".to_string(),
+        "movewindow" => match args.strip_prefix("mon:") {
+            Some(monitor) => format!("Move window to monitor {}", monitor),
+            None => format!("Move window {}", direction(args)?),
+        },
+        "swapwindow" => format!("Swap window {}", direction(args)?),
+        "resizeactive" => format!("Resize window by {}", args),
+        "moveactive" => format!("Move window by {}", args),
+        "togglefloating" => "Toggle floating".to_string(),
+        "setfloating" => "Make window floating".to_string(),
+        "settiled" => "Make window tiled".to_string(),
+        "fullscreen" => match first_arg {
+            "1" => "Toggle maximize".to_string(),
+            _ => "Toggle fullscreen".to_string(),
+        },
+        "fakefullscreen" => "Toggle fake fullscreen".to_string(),
+        "pseudo" => "Toggle pseudotiling".to_string(),
+        "pin" => "Pin window to all workspaces".to_string(),
+        "centerwindow" => "Center window".to_string(),
+        "togglesplit" => "Toggle split direction".to_string(),
+        "swapsplit" => "Swap split halves".to_string(),
+        "cyclenext" if args.contains("prev") => "Focus previous window".to_string(),
+        "cyclenext" => "Focus next window".to_string(),
+        "focusurgentorlast" => "Focus urgent or last window".to_string(),
+        "focuscurrentorlast" => "Focus last window".to_string(),
+        "bringactivetotop" => "Bring window to top".to_string(),
+        "togglegroup" => "Toggle window group".to_string(),
+        "changegroupactive" if args.starts_with('b') => "Previous window in group".to_string(),
+        "changegroupactive" => "Next window in group".to_string(),
+        "moveintogroup" => format!("Move window into group {}", direction(args)?),
+        "moveoutofgroup" => "Move window out of group".to_string(),
+        "lockgroups" => "Lock window groups".to_string(),
+        "layoutmsg" => format!("Layout: {}", args),
+        "submap" if args == "reset" => "Leave the submap".to_string(),
+        "submap" => format!("Enter the {} submap", args),
+        "pass" => format!("Pass the key to {}", args),
+        "global" => format!("Trigger global shortcut {}", args),
+        "dpms" => match first_arg {
+            "off" => "Turn displays off".to_string(),
+            "on" => "Turn displays on".to_string(),
+            _ => "Toggle displays".to_string(),
+        },
+        "exit" => "Exit Hyprland".to_string(),
+        "forcerendererreload" => "Reload the renderer".to_string(),
+        _ => return None,
+    };
+    
+    Some(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn known_dispatchers_are_described() {
+        let cases = [
+            ("exec", "[float] GDK_BACKEND=wayland /usr/bin/kitty --hold", "Run kitty"),
+            ("killactive", "", "Close window"),
+            ("workspace", "e+1", "Switch to the next workspace"),
+            ("movetoworkspacesilent", "special:scratch", "Move window to workspace scratch (silent)"),
+            ("movefocus", "l", "Focus window left"),
+            ("movewindow", "", "Move window with the mouse"),
+            ("movewindow", "mon:DP-1", "Move window to monitor DP-1"),
+            ("fullscreen", "1", "Toggle maximize"),
+            ("submap", "resize", "Enter the resize submap"),
+            ("submap", "reset", "Leave the submap"),
+        ];
+        for (dispatcher, args, description) in cases {
+            assert_eq!(describe(dispatcher, args).as_deref(), Some(description), "{} {}", dispatcher, args);
+        }
+    }
+    
+    #[test]
+    fn unknown_dispatchers_and_arguments_are_not_described() {
+        assert_eq!(describe("hy3:movefocus", "l"), None);
+        assert_eq!(describe("movefocus", "sideways"), None);
+        assert_eq!(describe("exec", ""), None);
+    }
+    
+    #[test]
+    fn dispatchers_are_grouped() {
+        assert_eq!(category("exec", "kitty"), DispatcherCategory::Launchers);
+        assert_eq!(category("exec", "wpctl set-volume @DEFAULT_AUDIO_SINK@ 5%+"), DispatcherCategory::Media);
+        assert_eq!(category("movetoworkspace", "3"), DispatcherCategory::Workspaces);
+        assert_eq!(category("togglefloating", ""), DispatcherCategory::Windows);
+        assert_eq!(category("hy3:makegroup", "h"), DispatcherCategory::System);
+    }
+}
@@ -0,0 +1,320 @@
+use anyhow::{anyhow, bail, Context, Result};
+use log::info;
+use regex::Regex;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::config_parser::{BindFlags, ConfigParser, Origin};
+
+/// The fields of a bind line as written in the config, before variable expansion
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BindLine {
+    /// `bind`, `binde`, `bindm`...
+    pub keyword: String,
+    pub modifiers: String,
+    pub key: String,
+    /// Description argument, only written for `bindd` style keywords
+    pub description: String,
+    pub dispatcher: String,
+    pub args: String,
+}
+
+impl BindLine {
+    /// Parse the raw text of a bind line
+    pub fn parse(line: &str) -> Option<Self> {
+        let bind_regex = Regex::new(r"^\s*(bind[a-z]*)\s*=\s*(.+)$").ok()?;
+        let (code, _) = ConfigParser::split_comment(line);
+        let cap = bind_regex.captures(&code)?;
+        
+        let keyword = cap[1].to_string();
+        let flags = BindFlags::from_suffix(&keyword[4..])?;
+        let field_count = if flags.has_description { 5 } else { 4 };
+        let fields: Vec<&str> = cap[2].splitn(field_count, ',').map(str::trim).collect();
+        let field = |idx: usize| fields.get(idx).copied().unwrap_or("").to_string();
+        
+        if flags.has_description {
+            Some(Self {
+                keyword,
+                modifiers: field(0),
+                key: field(1),
+                description: field(2),
+                dispatcher: field(3),
+                args: field(4),
+            })
+        } else {
+            Some(Self {
+                keyword,
+                modifiers: field(0),
+                key: field(1),
+                description: String::new(),
+                dispatcher: field(2),
+                args: field(3),
+            })
+        }
+    }
+    
+    /// Format the bind in Hyprland syntax, without indentation or comment
+    pub fn to_config(&self) -> Result<String> {
+        let flags = self.keyword
+            .strip_prefix("bind")
+            .and_then(BindFlags::from_suffix)
+            .ok_or_else(|| anyhow!("'{}' is not a valid bind keyword", self.keyword))?;
+        if self.key.trim().is_empty() {
+            bail!("The key must not be empty");
+        }
+        if self.dispatcher.trim().is_empty() {
+            bail!("The dispatcher must not be empty");
+        }
+        
+        // A single # starts a comment in Hyprland, so it has to be escaped
+        let escape = |text: &str| text.trim().replace('#', "##");
+        
+        let mut fields = vec![escape(&self.modifiers), escape(&self.key)];
+        if flags.has_description {
+            fields.push(escape(&self.description));
+        }
+        fields.push(escape(&self.dispatcher));
+        if !self.args.trim().is_empty() {
+            fields.push(escape(&self.args));
+        }
+        
+        Ok(format!("{} = {}", self.keyword, fields.join(", ")))
+    }
+}
+
+/// Copy a config file to the backup directory before it gets modified
+fn backup_file(path: &Path) -> Result<PathBuf> {
+    let cache_dir = std::env::var("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|_| std::env::var("HOME").map(|home| PathBuf::from(home).join(".cache")))
+        .context("Could not determine the cache directory for backups")?;
+    let backup_dir = cache_dir.join("hyprland-keybinds").join("backups");
+    fs::create_dir_all(&backup_dir)
+        .with_context(|| format!("Failed to create backup directory: {:?}", backup_dir))?;
+    
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or(0);
+    let file_name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    
+    // Never overwrite an earlier backup, even if the clock did not move on
+    let mut backup_path = backup_dir.join(format!("{}.{}.bak", file_name, timestamp));
+    let mut counter = 1;
+    while backup_path.exists() {
+        counter += 1;
+        backup_path = backup_dir.join(format!("{}.{}-{}.bak", file_name, timestamp, counter));
+    }
+    
+    fs::copy(path, &backup_path)
+        .with_context(|| format!("Failed to back up {:?}", path))?;
+    info!("Backed up {:?} to {:?}", path, backup_path);
+    Ok(backup_path)
+}
+
+/// Replace a config file by writing a temporary file next to it and renaming
+/// it over the original, so a failed write never leaves a truncated config.
+/// Symlinks are followed so the link itself stays in place.
+fn write_config_file(path: &Path, content: &str) -> Result<()> {
+    let target = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let file_name = target
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .ok_or_else(|| anyhow!("{:?} is not a file", path))?;
+    let temp_path = target.with_file_name(format!(".{}.hyprland-keybinds.tmp", file_name));
+    
+    let write = || -> std::io::Result<()> {
+        let mut file = fs::File::create(&temp_path)?;
+        file.write_all(content.as_bytes())?;
+        file.sync_all()?;
+        
+        // Keep the permissions of the file being replaced
+        if let Ok(metadata) = fs::metadata(&target) {
+            fs::set_permissions(&temp_path, metadata.permissions())?;
+        }
+        fs::rename(&temp_path, &target)
+    };
+    
+    write()
+        .inspect_err(|_| {
+            let _ = fs::remove_file(&temp_path);
+        })
+        .with_context(|| format!("Failed to write config file: {:?}", path))
+}
+
+/// Replace (or remove, for `None`) the line an origin points to. Fails if the
+/// line no longer matches the text it was parsed from.
+fn rewrite_line(origin: &Origin, replacement: Option<&str>) -> Result<()> {
+    let content = fs::read_to_string(&origin.file)
+        .with_context(|| format!("Failed to read config file: {:?}", origin.file))?;
+    
+    // Keep the original line endings of every line
+    let mut lines: Vec<&str> = content.split_inclusive('\n').collect();
+    let idx = origin.line.checked_sub(1).filter(|&idx| idx < lines.len())
+        .ok_or_else(|| anyhow!("Line {} no longer exists in {:?}", origin.line, origin.file))?;
+    
+    let current = lines[idx];
+    let body = current.trim_end_matches(['\n', '\r']);
+    if body != origin.text {
+        bail!(
+            "{} changed since it was loaded, reload and try again.\nExpected: {}\nFound: {}",
+            origin.display_short(), origin.text, body
+        );
+    }
+    let line_ending = &current[body.len()..];
+    
+    let new_line;
+    match replacement {
+        Some(text) => {
+            new_line = format!("{}{}", text, line_ending);
+            lines[idx] = &new_line;
+        }
+        None => {
+            lines.remove(idx);
+        }
+    }
+    
+    backup_file(&origin.file)?;
+    write_config_file(&origin.file, &lines.concat())
+}
+
+/// Replace a bind with new fields, keeping its indentation and trailing comment
+pub fn update_bind(origin: &Origin, bind: &BindLine) -> Result<()> {
+    let indentation: String = origin.text.chars().take_while(|c| c.is_whitespace()).collect();
+    
+    // Find where the trailing comment starts so it can be kept verbatim
+    let (code, comment) = ConfigParser::split_comment(&origin.text);
+    let comment_part = match comment {
+        Some(_) => {
+            let code_len = code.trim_end().replace('#', "##").len();
+            origin.text.get(code_len..).unwrap_or("").to_string()
+        }
+        None => String::new(),
+    };
+    
+    let new_line = format!("{}{}{}", indentation, bind.to_config()?, comment_part);
+    rewrite_line(origin, Some(&new_line))?;
+    info!("Updated {}: {}", origin.display_short(), new_line.trim());
+    Ok(())
+}
+
+/// Remove a bind line from its file
+pub fn delete_bind(origin: &Origin) -> Result<()> {
+    rewrite_line(origin, None)?;
+    info!("Deleted {}: {}", origin.display_short(), origin.text.trim());
+    Ok(())
+}
+
+/// Append a new bind to the end of a config file
+pub fn add_bind(file: &Path, bind: &BindLine) -> Result<()> {
+    let mut content = fs::read_to_string(file)
+        .with_context(|| format!("Failed to read config file: {:?}", file))?;
+    let line = bind.to_config()?;
+    
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    content.push_str(&line);
+    content.push('\n');
+    
+    backup_file(file)?;
+    write_config_file(file, &content)?;
+    info!("Added to {:?}: {}", file, line);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::{symlink, PermissionsExt};
+    use std::sync::OnceLock;
+    
+    /// Keep the backups of all tests in one temporary cache directory
+    fn use_test_cache_dir() {
+        static CACHE_DIR: OnceLock<PathBuf> = OnceLock::new();
+        CACHE_DIR.get_or_init(|| {
+            let dir = std::env::temp_dir().join(format!("hyprland-keybinds-test-{}", std::process::id()));
+            std::env::set_var("XDG_CACHE_HOME", &dir);
+            dir
+        });
+    }
+    
+    #[test]
+    fn bind_lines_round_trip() {
+        for line in [
+            "bind = SUPER, Q, killactive",
+            "bind = SUPER SHIFT, Return, exec, kitty --class float, --hold",
+            "bindel = , XF86AudioRaiseVolume, exec, wpctl set-volume @DEFAULT_AUDIO_SINK@ 5%+",
+            "bindm = SUPER, mouse:272, movewindow",
+            "bindd = SUPER, E, Open files, exec, nautilus",
+            "bind = SUPER, F1, exec, notify-send ## not a comment",
+        ] {
+            assert_eq!(BindLine::parse(line).unwrap().to_config().unwrap(), line);
+        }
+    }
+    
+    #[test]
+    fn bind_lines_are_normalised() {
+        let bind = BindLine::parse("  bindd=SUPER,E,Open files,exec,nautilus   # Files").unwrap();
+        assert_eq!(bind, BindLine {
+            keyword: "bindd".to_string(),
+            modifiers: "SUPER".to_string(),
+            key: "E".to_string(),
+            description: "Open files".to_string(),
+            dispatcher: "exec".to_string(),
+            args: "nautilus".to_string(),
+        });
+        assert_eq!(bind.to_config().unwrap(), "bindd = SUPER, E, Open files, exec, nautilus");
+        
+        assert_eq!(BindLine::parse("unbind = SUPER, Q"), None);
+        assert_eq!(BindLine::parse("bindx = SUPER, Q, killactive"), None);
+        assert!(BindLine { keyword: "bind".to_string(), ..Default::default() }.to_config().is_err());
+    }
+    
+    #[test]
+    fn update_keeps_comment_and_flags() {
+        use_test_cache_dir();
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("hyprland.conf");
+        let text = "    bindel = , XF86AudioRaiseVolume, exec, wpctl set-volume @DEFAULT_AUDIO_SINK@ 5%+  # Volume ## up";
+        fs::write(&file, format!("$mainMod = SUPER\n{}\nbind = SUPER, Q, killactive\n", text)).unwrap();
+        
+        let origin = Origin { file: file.clone(), line: 2, text: text.to_string() };
+        let mut bind = BindLine::parse(text).unwrap();
+        bind.args = "wpctl set-volume @DEFAULT_AUDIO_SINK@ 10%+".to_string();
+        update_bind(&origin, &bind).unwrap();
+        
+        assert_eq!(
+            fs::read_to_string(&file).unwrap(),
+            "$mainMod = SUPER\n    bindel = , XF86AudioRaiseVolume, exec, wpctl set-volume @DEFAULT_AUDIO_SINK@ 10%+  # Volume ## up\nbind = SUPER, Q, killactive\n"
+        );
+        
+        // The line changed, so the old origin no longer applies
+        assert!(update_bind(&origin, &bind).is_err());
+    }
+    
+    #[test]
+    fn write_replaces_symlink_target() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("binds.conf");
+        let link = dir.path().join("hyprland.conf");
+        fs::write(&target, "bind = SUPER, Q, killactive\n").unwrap();
+        fs::set_permissions(&target, fs::Permissions::from_mode(0o600)).unwrap();
+        symlink(&target, &link).unwrap();
+        
+        write_config_file(&link, "bind = SUPER, C, killactive\r\n").unwrap();
+        
+        assert!(fs::symlink_metadata(&link).unwrap().file_type().is_symlink());
+        assert_eq!(fs::read_to_string(&target).unwrap(), "bind = SUPER, C, killactive\r\n");
+        assert_eq!(fs::metadata(&target).unwrap().permissions().mode() & 0o777, 0o600);
+        // No temporary file is left behind
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 2);
+    }
+    
+    #[test]
+    fn write_to_missing_directory_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = dir.path().join("missing").join("hyprland.conf");
+        assert!(write_config_file(&missing, "bind = SUPER, Q, killactive\n").is_err());
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+}
@@ -0,0 +1,400 @@
+use anyhow::{anyhow, Context, Result};
+use log::{debug, info};
+use serde::Deserialize;
+use std::fs;
+use std::io::{Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::config_parser::{BindFlags, BindSource, Keybind};
+use crate::modifiers::{modifiers_from_mask, modmask_from_modifiers};
+
+/// A bind as reported by `hyprctl binds -j`
+#[derive(Debug, Deserialize)]
+struct LiveBind {
+    #[serde(default)]
+    locked: bool,
+    #[serde(default)]
+    mouse: bool,
+    #[serde(default)]
+    release: bool,
+    #[serde(default)]
+    repeat: bool,
+    #[serde(default, rename = "longPress")]
+    long_press: bool,
+    #[serde(default)]
+    non_consuming: bool,
+    #[serde(default)]
+    has_description: bool,
+    #[serde(default)]
+    modmask: u32,
+    #[serde(default)]
+    submap: String,
+    #[serde(default)]
+    key: String,
+    #[serde(default)]
+    keycode: i64,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    dispatcher: String,
+    #[serde(default)]
+    arg: String,
+}
+
+/// Client for the Hyprland request socket (`.socket.sock`)
+pub struct HyprlandIpc {
+    socket_path: PathBuf,
+}
+
+impl HyprlandIpc {
+    /// Create a client for a specific socket, e.g. a fake socket serving recorded replies
+    pub fn new(socket_path: impl Into<PathBuf>) -> Self {
+        Self {
+            socket_path: socket_path.into(),
+        }
+    }
+    
+    /// Locate the socket of the running Hyprland instance
+    pub fn from_env() -> Result<Self> {
+        instance_dir()
+            .map(|dir| dir.join(".socket.sock"))
+            .filter(|path| path.exists())
+            .map(Self::new)
+            .ok_or_else(|| anyhow!("Could not find the Hyprland socket, is Hyprland running?"))
+    }
+    
+    /// Send a raw request (e.g. `j/binds`) and return the reply
+    pub fn request(&self, command: &str) -> Result<String> {
+        debug!("Sending IPC request '{}' to {:?}", command, self.socket_path);
+        
+        let mut stream = UnixStream::connect(&self.socket_path)
+            .with_context(|| format!("Failed to connect to Hyprland socket: {:?}", self.socket_path))?;
+        stream.set_read_timeout(Some(Duration::from_secs(2)))?;
+        stream.set_write_timeout(Some(Duration::from_secs(2)))?;
+        
+        stream.write_all(command.as_bytes())
+            .context("Failed to send request to Hyprland")?;
+        
+        let mut reply = String::new();
+        stream.read_to_string(&mut reply)
+            .context("Failed to read reply from Hyprland")?;
+        
+        Ok(reply)
+    }
+    
+    /// Read the binds currently registered in the compositor
+    pub fn binds(&self) -> Result<Vec<Keybind>> {
+        let reply = self.request("j/binds")?;
+        let keybinds = parse_live_binds(&reply)?;
+        info!("Read {} live keybinds from Hyprland", keybinds.len());
+        Ok(keybinds)
+    }
+    
+    /// Run the dispatcher of a keybind as if it had been pressed
+    pub fn dispatch_keybind(&self, keybind: &Keybind) -> Result<()> {
+        let dispatcher = keybind.dispatcher();
+        if dispatcher.is_empty() {
+            return Err(anyhow!("{} has no dispatcher", keybind.combo_text()));
+        }
+        
+        info!("Running '{}' for {}", keybind.action, keybind.combo_text());
+        self.dispatch(format!("{} {}", dispatcher, keybind.args()).trim_end())
+    }
+    
+    /// Run a dispatcher, e.g. `submap reset`
+    pub fn dispatch(&self, command: &str) -> Result<()> {
+        let reply = self.request(&format!("dispatch {}", command))?;
+        if reply.trim() != "ok" {
+            return Err(anyhow!("Hyprland rejected 'dispatch {}': {}", command, reply.trim()));
+        }
+        Ok(())
+    }
+}
+
+/// Runtime directory of the running Hyprland instance. Newer Hyprland
+/// versions use XDG_RUNTIME_DIR, older ones /tmp/hypr.
+fn instance_dir() -> Option<PathBuf> {
+    let signature = std::env::var("HYPRLAND_INSTANCE_SIGNATURE").ok()?;
+    
+    let mut candidates = Vec::new();
+    if let Ok(runtime_dir) = std::env::var("XDG_RUNTIME_DIR") {
+        candidates.push(PathBuf::from(runtime_dir).join("hypr").join(&signature));
+    }
+    candidates.push(PathBuf::from("/tmp/hypr").join(&signature));
+    
+    candidates.into_iter().find(|dir| dir.is_dir())
+}
+
+/// The config file the running compositor was started with through
+/// `-c`/`--config`. `None` if Hyprland is not running or uses the default config.
+pub fn compositor_config_path() -> Option<PathBuf> {
+    // The lock file of the instance starts with the compositor's PID
+    let lock = fs::read_to_string(instance_dir()?.join("hyprland.lock")).ok()?;
+    let pid: u32 = lock.lines().next()?.trim().parse().ok()?;
+    
+    let cmdline = fs::read(format!("/proc/{}/cmdline", pid)).ok()?;
+    let args: Vec<String> = cmdline
+        .split(|&byte| byte == 0)
+        .map(|arg| String::from_utf8_lossy(arg).to_string())
+        .collect();
+    let path = args
+        .iter()
+        .position(|arg| arg == "-c" || arg == "--config")
+        .and_then(|idx| args.get(idx + 1))
+        .map(PathBuf::from)?;
+    
+    // Relative paths are relative to the directory Hyprland was started in
+    let path = if path.is_relative() {
+        fs::read_link(format!("/proc/{}/cwd", pid)).ok()?.join(path)
+    } else {
+        path
+    };
+    debug!("Running Hyprland (PID {}) was started with config {:?}", pid, path);
+    Some(path)
+}
+
+/// Parse the JSON reply of `hyprctl binds -j` into keybinds
+pub fn parse_live_binds(json: &str) -> Result<Vec<Keybind>> {
+    let live_binds: Vec<LiveBind> = serde_json::from_str(json)
+        .context("Failed to parse bind list from Hyprland")?;
+    
+    Ok(live_binds
+        .into_iter()
+        .enumerate()
+        .map(|(order, live_bind)| live_bind.into_keybind(order))
+        .collect())
+}
+
+impl LiveBind {
+    fn into_keybind(self, order: usize) -> Keybind {
+        let key = if self.key.is_empty() && self.keycode != 0 {
+            format!("code:{}", self.keycode)
+        } else {
+            self.key
+        };
+        
+        let action = if self.arg.is_empty() {
+            self.dispatcher
+        } else {
+            format!("{}, {}", self.dispatcher, self.arg)
+        };
+        
+        Keybind {
+            key,
+            modifiers: modifiers_from_mask(self.modmask),
+            action,
+            description: Some(self.description).filter(|d| !d.is_empty()),
+            flags: BindFlags {
+                locked: self.locked,
+                release: self.release,
+                long_press: self.long_press,
+                repeat: self.repeat,
+                non_consuming: self.non_consuming,
+                mouse: self.mouse,
+                has_description: self.has_description,
+                ..BindFlags::default()
+            },
+            submap: Some(self.submap).filter(|s| !s.is_empty()),
+            source: BindSource::LiveOnly,
+            origin: None,
+            order,
+            removed_by: None,
+        }
+    }
+}
+
+/// Check whether a bind from the config files and a live bind are the same bind
+fn same_bind(file_bind: &Keybind, live_bind: &Keybind) -> bool {
+    modmask_from_modifiers(&file_bind.modifiers) == modmask_from_modifiers(&live_bind.modifiers)
+        && file_bind.key.eq_ignore_ascii_case(&live_bind.key)
+        && file_bind.submap == live_bind.submap
+        && file_bind.dispatcher() == live_bind.dispatcher()
+        && file_bind.args() == live_bind.args()
+}
+
+/// Compare the binds parsed from the config files with the binds of the running
+/// compositor. File binds are marked `Both` or `FileOnly`, and live binds without
+/// a matching file bind are appended as `LiveOnly`.
+pub fn compare_with_live(file_binds: &[Keybind], live_binds: &[Keybind]) -> Vec<Keybind> {
+    let mut unmatched: Vec<&Keybind> = live_binds.iter().collect();
+    let mut merged = Vec::with_capacity(file_binds.len() + live_binds.len());
+    
+    for file_bind in file_binds {
+        let mut keybind = file_bind.clone();
+        // Removed binds are not expected in the compositor
+        if file_bind.removed_by.is_some() {
+            merged.push(keybind);
+            continue;
+        }
+        match unmatched.iter().position(|live_bind| same_bind(file_bind, live_bind)) {
+            Some(idx) => {
+                unmatched.remove(idx);
+                keybind.source = BindSource::Both;
+            }
+            None => keybind.source = BindSource::FileOnly,
+        }
+        merged.push(keybind);
+    }
+    
+    debug!("{} live keybinds are not in the config files", unmatched.len());
+    merged.extend(unmatched.into_iter().map(|live_bind| Keybind {
+        source: BindSource::LiveOnly,
+        ..live_bind.clone()
+    }));
+    
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config_parser::Origin;
+    use std::os::unix::net::UnixListener;
+    use std::path::Path;
+    use std::thread::{self, JoinHandle};
+    
+    /// Reply of `hyprctl binds -j` recorded from a running compositor
+    const RECORDED_BINDS: &str = r#"[
+        {"locked": false, "mouse": false, "release": false, "repeat": false, "longPress": false,
+         "non_consuming": false, "has_description": false, "modmask": 64, "submap": "",
+         "key": "Return", "keycode": 0, "catch_all": false, "description": "",
+         "dispatcher": "exec", "arg": "kitty"},
+        {"locked": false, "mouse": false, "release": false, "repeat": true, "longPress": false,
+         "non_consuming": false, "has_description": false, "modmask": 65, "submap": "resize",
+         "key": "right", "keycode": 0, "catch_all": false, "description": "",
+         "dispatcher": "resizeactive", "arg": "10 0"},
+        {"locked": true, "mouse": false, "release": false, "repeat": false, "longPress": false,
+         "non_consuming": false, "has_description": false, "modmask": 0, "submap": "",
+         "key": "", "keycode": 121, "catch_all": false, "description": "",
+         "dispatcher": "exec", "arg": "wpctl set-mute @DEFAULT_AUDIO_SINK@ toggle"}
+    ]"#;
+    
+    /// Serve one reply per connection on a socket at `path`, returning the requests received
+    fn fake_socket(path: &Path, replies: &[&str]) -> JoinHandle<Vec<String>> {
+        let listener = UnixListener::bind(path).unwrap();
+        let replies: Vec<String> = replies.iter().map(|reply| reply.to_string()).collect();
+        
+        thread::spawn(move || {
+            let mut requests = Vec::new();
+            for reply in replies {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut buffer = [0; 1024];
+                let len = stream.read(&mut buffer).unwrap();
+                requests.push(String::from_utf8_lossy(&buffer[..len]).to_string());
+                stream.write_all(reply.as_bytes()).unwrap();
+            }
+            requests
+        })
+    }
+    
+    fn file_bind(modifiers: &[&str], key: &str, action: &str, submap: Option<&str>) -> Keybind {
+        Keybind {
+            key: key.to_string(),
+            modifiers: modifiers.iter().map(|modifier| modifier.to_string()).collect(),
+            action: action.to_string(),
+            description: None,
+            flags: BindFlags::default(),
+            submap: submap.map(str::to_string),
+            source: BindSource::File,
+            origin: None,
+            order: 0,
+            removed_by: None,
+        }
+    }
+    
+    #[test]
+    fn reads_binds_from_socket() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join(".socket.sock");
+        let server = fake_socket(&socket, &[RECORDED_BINDS]);
+        
+        let binds = HyprlandIpc::new(&socket).binds().unwrap();
+        assert_eq!(server.join().unwrap(), vec!["j/binds"]);
+        
+        assert_eq!(binds.len(), 3);
+        assert_eq!(binds[0].modifiers, vec!["SUPER"]);
+        assert_eq!(binds[0].action, "exec, kitty");
+        assert_eq!(binds[0].submap, None);
+        assert_eq!(binds[1].modifiers, vec!["SUPER", "SHIFT"]);
+        assert_eq!(binds[1].submap.as_deref(), Some("resize"));
+        assert!(binds[1].flags.repeat);
+        assert_eq!(binds[2].key, "code:121");
+        assert!(binds[2].flags.locked);
+        assert!(binds.iter().all(|bind| bind.source == BindSource::LiveOnly));
+    }
+    
+    #[test]
+    fn dispatches_keybind_actions() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join(".socket.sock");
+        let server = fake_socket(&socket, &["ok", "ok", "ok"]);
+        let ipc = HyprlandIpc::new(&socket);
+        
+        ipc.dispatch_keybind(&file_bind(&["SUPER"], "Return", "exec, kitty --single-instance", None)).unwrap();
+        ipc.dispatch_keybind(&file_bind(&["SUPER"], "Q", "killactive", None)).unwrap();
+        ipc.dispatch("submap reset").unwrap();
+        
+        assert_eq!(server.join().unwrap(), vec![
+            "dispatch exec kitty --single-instance",
+            "dispatch killactive",
+            "dispatch submap reset",
+        ]);
+    }
+    
+    #[test]
+    fn dispatch_error_reply_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join(".socket.sock");
+        let server = fake_socket(&socket, &["Invalid dispatcher"]);
+        
+        let result = HyprlandIpc::new(&socket).dispatch_keybind(&file_bind(&["SUPER"], "X", "nosuchdispatcher, 1", None));
+        assert_eq!(server.join().unwrap(), vec!["dispatch nosuchdispatcher 1"]);
+        let err = result.unwrap_err().to_string();
+        assert!(err.contains("Invalid dispatcher"), "{}", err);
+    }
+    
+    #[test]
+    fn keybind_without_dispatcher_is_not_sent() {
+        let ipc = HyprlandIpc::new("/nonexistent/.socket.sock");
+        assert!(ipc.dispatch_keybind(&file_bind(&["SUPER"], "X", "", None)).is_err());
+    }
+    
+    #[test]
+    fn rejects_invalid_reply() {
+        assert!(parse_live_binds("unknown request").is_err());
+    }
+    
+    #[test]
+    fn classifies_file_and_live_binds() {
+        let live_binds = parse_live_binds(RECORDED_BINDS).unwrap();
+        let mut removed = file_bind(&["SUPER"], "Q", "killactive", None);
+        removed.removed_by = Some(Origin {
+            file: PathBuf::from("hyprland.conf"),
+            line: 12,
+            text: "unbind = SUPER, Q".to_string(),
+        });
+        let file_binds = vec![
+            // Modifier spelling and key case differ from the compositor's
+            file_bind(&["SUPER"], "return", "exec, kitty", None),
+            file_bind(&["SUPER_SHIFT"], "right", "resizeactive, 10 0", Some("resize")),
+            // Same combo outside the submap is a different bind
+            file_bind(&["SUPER", "SHIFT"], "right", "resizeactive, 10 0", None),
+            file_bind(&["SUPER"], "E", "exec, nautilus", None),
+            removed,
+        ];
+        
+        let merged = compare_with_live(&file_binds, &live_binds);
+        let sources: Vec<BindSource> = merged.iter().map(|bind| bind.source).collect();
+        assert_eq!(sources, vec![
+            BindSource::Both,
+            BindSource::Both,
+            BindSource::FileOnly,
+            BindSource::FileOnly,
+            BindSource::File,
+            BindSource::LiveOnly,
+        ]);
+        assert_eq!(merged[5].key, "code:121");
+    }
+}
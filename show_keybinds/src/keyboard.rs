@@ -0,0 +1,352 @@
+use gtk::gdk;
+use gtk::prelude::*;
+use gtk::{Box as GtkBox, Grid, Label, Orientation, ToggleButton};
+use log::debug;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::config_parser::Keybind;
+use crate::dispatchers::DispatcherCategory;
+use crate::pointer;
+use crate::modifiers::modmask_from_modifiers;
+
+/// Grid columns per key unit, so keys can be 1.25 or 1.75 units wide
+const COLUMNS_PER_UNIT: f64 = 4.0;
+
+/// A key of the bundled geometry: XKB keycode, fallback keysym name and width in key units.
+/// Keycode 0 is an empty gap.
+#[derive(Debug, Clone, Copy)]
+struct KeyDef {
+    keycode: u32,
+    name: &'static str,
+    width: f64,
+}
+
+const fn key(keycode: u32, name: &'static str, width: f64) -> KeyDef {
+    KeyDef { keycode, name, width }
+}
+
+const GAP: KeyDef = key(0, "", 1.0);
+
+const FUNCTION_ROW: &[KeyDef] = &[
+    key(9, "Escape", 1.0), key(67, "F1", 1.0), key(68, "F2", 1.0), key(69, "F3", 1.0), key(70, "F4", 1.0),
+    key(71, "F5", 1.0), key(72, "F6", 1.0), key(73, "F7", 1.0), key(74, "F8", 1.0), key(75, "F9", 1.0),
+    key(76, "F10", 1.0), key(95, "F11", 1.0), key(96, "F12", 1.0), key(107, "Print", 1.0),
+    key(110, "Home", 1.0), key(115, "End", 1.0),
+];
+
+const NUMBER_ROW: &[KeyDef] = &[
+    key(49, "grave", 1.0), key(10, "1", 1.0), key(11, "2", 1.0), key(12, "3", 1.0), key(13, "4", 1.0),
+    key(14, "5", 1.0), key(15, "6", 1.0), key(16, "7", 1.0), key(17, "8", 1.0), key(18, "9", 1.0),
+    key(19, "0", 1.0), key(20, "minus", 1.0), key(21, "equal", 1.0), key(22, "BackSpace", 2.0),
+    key(119, "Delete", 1.0),
+];
+
+const BOTTOM_ROW: &[KeyDef] = &[
+    key(37, "Control_L", 1.25), key(133, "Super_L", 1.25), key(64, "Alt_L", 1.25), key(65, "space", 6.25),
+    key(108, "Alt_R", 1.0), key(135, "Menu", 1.0), key(105, "Control_R", 1.0), key(113, "Left", 1.0),
+    key(116, "Down", 1.0), key(114, "Right", 1.0),
+];
+
+const ANSI: &[&[KeyDef]] = &[
+    FUNCTION_ROW,
+    NUMBER_ROW,
+    &[
+        key(23, "Tab", 1.5), key(24, "q", 1.0), key(25, "w", 1.0), key(26, "e", 1.0), key(27, "r", 1.0),
+        key(28, "t", 1.0), key(29, "y", 1.0), key(30, "u", 1.0), key(31, "i", 1.0), key(32, "o", 1.0),
+        key(33, "p", 1.0), key(34, "bracketleft", 1.0), key(35, "bracketright", 1.0), key(51, "backslash", 1.5),
+        key(112, "Prior", 1.0),
+    ],
+    &[
+        key(66, "Caps_Lock", 1.75), key(38, "a", 1.0), key(39, "s", 1.0), key(40, "d", 1.0), key(41, "f", 1.0),
+        key(42, "g", 1.0), key(43, "h", 1.0), key(44, "j", 1.0), key(45, "k", 1.0), key(46, "l", 1.0),
+        key(47, "semicolon", 1.0), key(48, "apostrophe", 1.0), key(36, "Return", 2.25), key(117, "Next", 1.0),
+    ],
+    &[
+        key(50, "Shift_L", 2.25), key(52, "z", 1.0), key(53, "x", 1.0), key(54, "c", 1.0), key(55, "v", 1.0),
+        key(56, "b", 1.0), key(57, "n", 1.0), key(58, "m", 1.0), key(59, "comma", 1.0), key(60, "period", 1.0),
+        key(61, "slash", 1.0), key(62, "Shift_R", 1.75), key(111, "Up", 1.0), GAP,
+    ],
+    BOTTOM_ROW,
+];
+
+/// ISO boards have a taller Return key, an extra key next to left Shift
+/// and the backslash key on the home row
+const ISO: &[&[KeyDef]] = &[
+    FUNCTION_ROW,
+    NUMBER_ROW,
+    &[
+        key(23, "Tab", 1.5), key(24, "q", 1.0), key(25, "w", 1.0), key(26, "e", 1.0), key(27, "r", 1.0),
+        key(28, "t", 1.0), key(29, "y", 1.0), key(30, "u", 1.0), key(31, "i", 1.0), key(32, "o", 1.0),
+        key(33, "p", 1.0), key(34, "bracketleft", 1.0), key(35, "bracketright", 1.0), key(36, "Return", 1.5),
+        key(112, "Prior", 1.0),
+    ],
+    &[
+        key(66, "Caps_Lock", 1.75), key(38, "a", 1.0), key(39, "s", 1.0), key(40, "d", 1.0), key(41, "f", 1.0),
+        key(42, "g", 1.0), key(43, "h", 1.0), key(44, "j", 1.0), key(45, "k", 1.0), key(46, "l", 1.0),
+        key(47, "semicolon", 1.0), key(48, "apostrophe", 1.0), key(51, "numbersign", 1.0), key(36, "Return", 1.25),
+        key(117, "Next", 1.0),
+    ],
+    &[
+        key(50, "Shift_L", 1.25), key(94, "less", 1.0), key(52, "z", 1.0), key(53, "x", 1.0), key(54, "c", 1.0),
+        key(55, "v", 1.0), key(56, "b", 1.0), key(57, "n", 1.0), key(58, "m", 1.0), key(59, "comma", 1.0),
+        key(60, "period", 1.0), key(61, "slash", 1.0), key(62, "Shift_R", 1.75), key(111, "Up", 1.0), GAP,
+    ],
+    BOTTOM_ROW,
+];
+
+/// Modifier layers that can be toggled, with the keysyms of their keys
+const LAYER_MODIFIERS: &[(&str, u32, &[&str])] = &[
+    ("SUPER", 1 << 6, &["Super_L", "Super_R"]),
+    ("SHIFT", 1 << 0, &["Shift_L", "Shift_R"]),
+    ("CTRL", 1 << 2, &["Control_L", "Control_R"]),
+    ("ALT", 1 << 3, &["Alt_L", "Alt_R"]),
+];
+
+/// A key on the map with the keysym it produces in the current layout
+struct MapKey {
+    keycode: u32,
+    keysym: String,
+    label: Label,
+}
+
+/// Keyboard map that colours the keys bound under the selected modifiers
+pub struct KeyboardView {
+    pub widget: GtkBox,
+    grid: Grid,
+    layer_chips: Vec<(u32, ToggleButton)>,
+    summary_label: Label,
+    keys: RefCell<Vec<MapKey>>,
+    keybinds: RefCell<Vec<Keybind>>,
+}
+
+/// Look up the keysym of a keycode in the current XKB layout, without modifiers
+fn layout_keysym(keycode: u32) -> Option<String> {
+    let entries = gdk::Display::default()?.map_keycode(keycode)?;
+    let (_, keyval) = entries.into_iter().find(|(key, _)| key.level() == 0 && key.group() == 0)?;
+    keyval.name().map(|name| name.to_string())
+}
+
+/// Short text printed on a key
+fn key_label(keysym: &str) -> String {
+    let short = match keysym {
+        "Escape" => "Esc",
+        "BackSpace" => "Backspace",
+        "Return" => "Enter",
+        "Caps_Lock" => "Caps",
+        "Shift_L" | "Shift_R" => "Shift",
+        "Control_L" | "Control_R" => "Ctrl",
+        "Super_L" | "Super_R" => "Super",
+        "Alt_L" => "Alt",
+        "Alt_R" | "ISO_Level3_Shift" => "AltGr",
+        "Prior" => "PgUp",
+        "Next" => "PgDn",
+        "Delete" => "Del",
+        "Left" => "←",
+        "Up" => "↑",
+        "Down" => "↓",
+        "Right" => "→",
+        "space" => "Space",
+        _ => "",
+    };
+    if !short.is_empty() {
+        return short.to_string();
+    }
+    
+    // Printable keys show their character
+    match gdk::Key::from_name(keysym).and_then(|keyval| keyval.to_unicode()) {
+        Some(c) if !c.is_control() && !c.is_whitespace() => c.to_uppercase().to_string(),
+        _ => keysym.to_string(),
+    }
+}
+
+impl KeyboardView {
+    pub fn new() -> Rc<Self> {
+        let widget = GtkBox::new(Orientation::Vertical, 10);
+        widget.set_margin_top(10);
+        widget.set_margin_bottom(10);
+        widget.set_margin_start(20);
+        widget.set_margin_end(20);
+        
+        // Modifier chips and the geometry switch
+        let controls_box = GtkBox::new(Orientation::Horizontal, 6);
+        let mut layer_chips = Vec::new();
+        for (name, mask, _) in LAYER_MODIFIERS {
+            let chip = ToggleButton::with_label(name);
+            chip.add_css_class("search-button");
+            chip.set_active(*name == "SUPER");
+            controls_box.append(&chip);
+            layer_chips.push((*mask, chip));
+        }
+        
+        let geometry_dropdown = gtk::DropDown::from_strings(&["ANSI", "ISO"]);
+        geometry_dropdown.set_halign(gtk::Align::End);
+        geometry_dropdown.set_hexpand(true);
+        geometry_dropdown.set_tooltip_text(Some("Keyboard geometry"));
+        controls_box.append(&geometry_dropdown);
+        widget.append(&controls_box);
+        
+        let grid = Grid::new();
+        grid.set_row_spacing(4);
+        grid.set_column_spacing(0);
+        grid.set_column_homogeneous(true);
+        grid.set_row_homogeneous(true);
+        widget.append(&grid);
+        
+        // One chip per category explaining the colours, mouse binds have no key
+        let legend_box = GtkBox::new(Orientation::Horizontal, 6);
+        for category in DispatcherCategory::ALL.into_iter().filter(|&c| c != DispatcherCategory::Pointer) {
+            let legend = Label::new(Some(category.name()));
+            legend.add_css_class("keyboard-legend");
+            legend.add_css_class(&format!("keyboard-legend-{}", category.css_name()));
+            legend_box.append(&legend);
+        }
+        widget.append(&legend_box);
+        
+        let summary_label = Label::new(None);
+        summary_label.add_css_class("section-subtitle");
+        summary_label.set_xalign(0.0);
+        summary_label.set_wrap(true);
+        widget.append(&summary_label);
+        
+        let view = Rc::new(Self {
+            widget,
+            grid,
+            layer_chips,
+            summary_label,
+            keys: RefCell::new(Vec::new()),
+            keybinds: RefCell::new(Vec::new()),
+        });
+        view.build_keys(ANSI);
+        
+        for (_, chip) in &view.layer_chips {
+            let view_weak = Rc::downgrade(&view);
+            chip.connect_toggled(move |_| {
+                if let Some(view) = view_weak.upgrade() {
+                    view.paint();
+                }
+            });
+        }
+        
+        let view_weak = Rc::downgrade(&view);
+        geometry_dropdown.connect_selected_notify(move |dropdown| {
+            if let Some(view) = view_weak.upgrade() {
+                view.build_keys(if dropdown.selected() == 1 { ISO } else { ANSI });
+                view.paint();
+            }
+        });
+        
+        view
+    }
+    
+    /// Lay out the keys of a geometry in the grid
+    fn build_keys(&self, geometry: &[&[KeyDef]]) {
+        while let Some(child) = self.grid.first_child() {
+            self.grid.remove(&child);
+        }
+        
+        let mut keys = Vec::new();
+        for (row, row_keys) in geometry.iter().enumerate() {
+            let mut column = 0;
+            for def in row_keys.iter() {
+                let span = (def.width * COLUMNS_PER_UNIT).round() as i32;
+                if def.keycode != 0 {
+                    let keysym = layout_keysym(def.keycode).unwrap_or_else(|| def.name.to_string());
+                    let label = Label::new(Some(&key_label(&keysym)));
+                    label.add_css_class("keyboard-key");
+                    label.set_margin_start(2);
+                    label.set_margin_end(2);
+                    label.set_ellipsize(gtk::pango::EllipsizeMode::End);
+                    self.grid.attach(&label, column, row as i32, span, 1);
+                    keys.push(MapKey { keycode: def.keycode, keysym, label });
+                }
+                column += span;
+            }
+        }
+        
+        debug!("Built keyboard map with {} keys", keys.len());
+        *self.keys.borrow_mut() = keys;
+    }
+    
+    /// Show a new set of keybinds on the map
+    pub fn update(&self, keybinds: &[Keybind]) {
+        *self.keybinds.borrow_mut() = keybinds.to_vec();
+        self.paint();
+    }
+    
+    /// Modmask of the selected layer chips
+    fn layer_mask(&self) -> u32 {
+        self.layer_chips
+            .iter()
+            .filter(|(_, chip)| chip.is_active())
+            .fold(0, |mask, (bit, _)| mask | bit)
+    }
+    
+    /// Colour the keys bound in the selected layer and set their tooltips
+    fn paint(&self) {
+        let layer = self.layer_mask();
+        let keybinds = self.keybinds.borrow();
+        
+        // Only global binds, submaps have their own keymap
+        let layer_binds: Vec<&Keybind> = keybinds
+            .iter()
+            .filter(|k| k.submap.is_none() && modmask_from_modifiers(&k.modifiers) == layer)
+            .collect();
+        let held_keys: Vec<&str> = LAYER_MODIFIERS
+            .iter()
+            .filter(|(_, mask, _)| layer & mask != 0)
+            .flat_map(|(_, _, keysyms)| keysyms.iter().copied())
+            .collect();
+        
+        let mut bound_keys = 0;
+        let mut shown = vec![false; layer_binds.len()];
+        for map_key in self.keys.borrow().iter() {
+            let label = &map_key.label;
+            label.remove_css_class("keyboard-key-modifier");
+            for category in DispatcherCategory::ALL {
+                label.remove_css_class(&format!("keyboard-key-{}", category.css_name()));
+            }
+            label.set_tooltip_text(None);
+            
+            if held_keys.contains(&map_key.keysym.as_str()) {
+                label.add_css_class("keyboard-key-modifier");
+            }
+            
+            let code = format!("code:{}", map_key.keycode);
+            let on_key: Vec<usize> = layer_binds
+                .iter()
+                .enumerate()
+                .filter(|(_, k)| k.key.eq_ignore_ascii_case(&map_key.keysym) || k.key.eq_ignore_ascii_case(&code))
+                .map(|(idx, _)| idx)
+                .collect();
+            let Some(&first) = on_key.first() else {
+                continue;
+            };
+            
+            bound_keys += 1;
+            label.add_css_class(&format!("keyboard-key-{}", layer_binds[first].category().css_name()));
+            let tooltip: Vec<String> = on_key
+                .iter()
+                .map(|&idx| {
+                    shown[idx] = true;
+                    let keybind = layer_binds[idx];
+                    format!("{}: {}", keybind.combo_text(), keybind.summary())
+                })
+                .collect();
+            label.set_tooltip_text(Some(&tooltip.join("\n")));
+        }
+        
+        // Binds on keys that are not part of the map, like media keys or mouse buttons
+        let others: Vec<&str> = layer_binds
+            .iter()
+            .zip(&shown)
+            .filter(|(_, shown)| !**shown)
+            .map(|(k, _)| pointer::mouse_input(&k.key).map(|input| input.name).unwrap_or(k.key.as_str()))
+            .collect();
+        let mut summary = format!("{} keys bound on this layer", bound_keys);
+        if !others.is_empty() {
+            summary.push_str(&format!(" · also bound: {}", others.join(", ")));
+        }
+        self.summary_label.set_text(&summary);
+    }
+}
@@ -1,12 +1,30 @@
 use gtk::prelude::*;
 use gtk::{Application, ApplicationWindow, CssProvider};
 use anyhow::{Result, anyhow};
-use log::{info, error};
+use log::error;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::rc::Rc;
 
+mod capture;
+mod cheatsheet;
+mod cli;
 mod config_parser;
+mod conflicts;
+mod editor;
+mod dispatchers;
+mod ipc;
+mod keyboard;
+mod modifiers;
+mod pointer;
+mod search;
+mod state;
+mod theme;
 mod ui;
+mod watcher;
 
-use config_parser::ConfigParser;
+use state::AppState;
 
 const APP_ID: &str = "com.github.hyprland_keybinds";
 
@@ -14,6 +32,19 @@ fn main() -> Result<()> {
     // Initialize logger
     env_logger::init();
     
+    // Handle the headless command-line modes before touching GTK
+    let options = cli::Options::parse(std::env::args().skip(1))?;
+    if options.help {
+        cli::print_usage();
+        return Ok(());
+    }
+    if let Some(format) = options.list_format {
+        return cli::print_keybinds(format, options.config.as_deref());
+    }
+    if let Some(path) = &options.cheat_sheet {
+        return cli::export_cheat_sheet(path, options.config.as_deref());
+    }
+    
     // Initialize GTK
     gtk::init()?;
     
@@ -26,10 +57,13 @@ fn main() -> Result<()> {
         .build();
     
     // Connect to "activate" signal
-    app.connect_activate(build_ui);
+    let config = options.config;
+    app.connect_activate(move |app| build_ui(app, config.clone()));
     
-    // Run the application
-    let status = app.run();
+    // Run the application. Our arguments were handled above, GTK would
+    // reject them as unknown options.
+    let program = std::env::args().next().unwrap_or_default();
+    let status = app.run_with_args(&[program]);
     if status == 0.into() {
         Ok(())
     } else {
@@ -37,20 +71,7 @@ fn main() -> Result<()> {
     }
 }
 
-fn build_ui(app: &Application) {
-    // Parse keybinds from config
-    let mut config_parser = ConfigParser::new();
-    let keybinds = match config_parser.parse_keybinds() {
-        Ok(keybinds) => {
-            info!("Found {} keybinds", keybinds.len());
-            keybinds
-        },
-        Err(e) => {
-            error!("Failed to parse keybinds: {}", e);
-            Vec::new()
-        }
-    };
-    
+fn build_ui(app: &Application, config: Option<PathBuf>) {
     // Create a window with fixed size and floating behavior
     let window = ApplicationWindow::builder()
         .application(app)
@@ -96,13 +117,60 @@ fn build_ui(app: &Application) {
     search_button.set_margin_end(20);
     search_button.set_valign(gtk::Align::Center);
     
+    // Create toggle to compare with the binds of the running compositor
+    let live_button = gtk::ToggleButton::with_label("Live");
+    live_button.add_css_class("search-button");
+    live_button.set_valign(gtk::Align::Center);
+    live_button.set_tooltip_text(Some("Compare with the keybinds loaded in the running Hyprland"));
+    
+    // Create toggle to also show binds removed by unbind
+    let removed_button = gtk::ToggleButton::with_label("Removed");
+    removed_button.add_css_class("search-button");
+    removed_button.set_valign(gtk::Align::Center);
+    removed_button.set_tooltip_text(Some("Also show keybinds removed by unbind"));
+    
+    // Create button to export a printable cheat sheet
+    let export_button = gtk::Button::from_icon_name("document-save-symbolic");
+    export_button.add_css_class("search-button");
+    export_button.set_valign(gtk::Align::Center);
+    export_button.set_tooltip_text(Some("Export cheat sheet"));
+    
+    // Create toggle to switch between the list and the keyboard map
+    let keyboard_button = gtk::ToggleButton::with_label("Keyboard");
+    keyboard_button.add_css_class("search-button");
+    keyboard_button.set_valign(gtk::Align::Center);
+    keyboard_button.set_tooltip_text(Some("Show bound keys on a keyboard"));
+    
+    // Create toggle to find a bind by pressing its key combination
+    let capture_button = gtk::ToggleButton::new();
+    capture_button.set_icon_name("input-keyboard-symbolic");
+    capture_button.add_css_class("search-button");
+    capture_button.set_valign(gtk::Align::Center);
+    capture_button.set_tooltip_text(Some("Press a shortcut to find it"));
+    
+    // Create button to add a new keybind
+    let add_button = gtk::Button::from_icon_name("list-add-symbolic");
+    add_button.add_css_class("search-button");
+    add_button.set_valign(gtk::Align::Center);
+    add_button.set_tooltip_text(Some("Add keybind"));
+    
     // Add elements to header
     header_box.append(&title_box);
+    header_box.append(&add_button);
+    header_box.append(&export_button);
+    header_box.append(&keyboard_button);
+    header_box.append(&live_button);
+    header_box.append(&removed_button);
+    header_box.append(&capture_button);
     header_box.append(&search_button);
     
     // Create search entry (initially hidden)
     let search_entry = gtk::SearchEntry::new();
     search_entry.set_placeholder_text(Some("Search keybinds..."));
+    search_entry.set_tooltip_text(Some(
+        "Free text is fuzzy matched. Use super+shift+q for key combinations,\n\
+         dispatcher:exec, file:keybinds.conf or submap:resize to filter.",
+    ));
     search_entry.set_margin_start(20);
     search_entry.set_margin_end(5);
     search_entry.set_margin_top(10);
@@ -127,9 +195,18 @@ fn build_ui(app: &Application) {
     search_container.append(&search_entry);
     search_container.append(&close_button);
     
+    // Create label showing the combo pressed in capture mode (initially hidden)
+    let capture_label = gtk::Label::new(None);
+    capture_label.add_css_class("capture-status");
+    capture_label.set_visible(false);
+    capture_label.set_xalign(0.0);
+    capture_label.set_margin_start(20);
+    capture_label.set_margin_top(10);
+    
     // Add header and search to main box
     main_box.append(&header_box);
     main_box.append(&search_container);
+    main_box.append(&capture_label);
     
     // Connect search button click
     let search_container_weak = search_container.downgrade();
@@ -170,126 +247,136 @@ fn build_ui(app: &Application) {
     scrolled_window.set_hexpand(true);
     scrolled_window.set_policy(gtk::PolicyType::Automatic, gtk::PolicyType::Automatic);
     
-    // Create a FlowBox to arrange keybinds in a grid
-    let keybind_flow = gtk::FlowBox::new();
-    keybind_flow.set_selection_mode(gtk::SelectionMode::None);
-    keybind_flow.set_homogeneous(false);
-    keybind_flow.set_column_spacing(10);
-    keybind_flow.set_row_spacing(10);
-    keybind_flow.set_min_children_per_line(1);
-    keybind_flow.set_max_children_per_line(4);
-    keybind_flow.set_activate_on_single_click(false);
-    keybind_flow.set_margin_start(10);
-    keybind_flow.set_margin_end(10);
-    keybind_flow.set_margin_top(10);
-    keybind_flow.set_margin_bottom(10);
-    keybind_flow.set_valign(gtk::Align::Start);
-    keybind_flow.set_halign(gtk::Align::Fill);
-    keybind_flow.set_vexpand(true);
-    keybind_flow.set_hexpand(true);
-    
-    // Populate keybind grid
-    for keybind in &keybinds {
-        // Create a frame for better visibility
-        let frame = gtk::Frame::new(None);
-        frame.set_size_request(180, 80); // Sabit genişlik ve minimum yükseklik
-        
-        // Create a box for this keybind
-        let keybind_box = gtk::Box::new(gtk::Orientation::Vertical, 3);
-        keybind_box.set_margin_top(8);
-        keybind_box.set_margin_bottom(8);
-        keybind_box.set_margin_start(10);
-        keybind_box.set_margin_end(10);
-        keybind_box.set_vexpand(true);  // Dikey genişleme
-        keybind_box.set_valign(gtk::Align::Center);  // Dikey ortalama
-        
-        // Create the key combo label
-        let key_combo = if keybind.modifiers.is_empty() {
-            keybind.key.clone()
-        } else {
-            format!("{} + {}", keybind.modifiers.join(" + "), keybind.key)
-        };
-        
-        let key_label = gtk::Label::new(Some(&key_combo));
-        key_label.add_css_class("keybind-combo");
-        key_label.set_xalign(0.0);
-        key_label.set_ellipsize(gtk::pango::EllipsizeMode::End);
-        
-        // Create the action label - simplify it for display
-        let display_action = if keybind.action.starts_with("exec") {
-            if let Some(first_comma) = keybind.action.find(',') {
-                if let Some(command) = keybind.action[first_comma+1..].trim().split_whitespace().next() {
-                    command.to_string()
-                } else {
-                    keybind.action.clone()
-                }
-            } else {
-                keybind.action.clone()
-            }
-        } else {
-            keybind.action.clone()
-        };
-        
-        let action_label = gtk::Label::new(Some(&display_action));
-        action_label.add_css_class("keybind-action");
-        action_label.set_xalign(0.0);
-        action_label.set_ellipsize(gtk::pango::EllipsizeMode::End);
-        
-        // Add both labels to the box
-        keybind_box.append(&key_label);
-        keybind_box.append(&action_label);
-        
-        // Add the box to the frame
-        frame.set_child(Some(&keybind_box));
-        
-        // Add the frame to the flow box
-        keybind_flow.insert(&frame, -1);
-    }
+    // Create a container with one collapsible section per submap
+    let sections_box = gtk::Box::new(gtk::Orientation::Vertical, 6);
+    sections_box.set_margin_start(10);
+    sections_box.set_margin_end(10);
+    sections_box.set_margin_top(10);
+    sections_box.set_margin_bottom(10);
     
-    // Add flow box to scrolled window
-    scrolled_window.set_child(Some(&keybind_flow));
-    main_box.append(&scrolled_window);
+    // Add sections to scrolled window
+    scrolled_window.set_child(Some(&sections_box));
+    
+    // Conflicts are shown above the list, the box stays empty without any
+    let conflicts_box = gtk::Box::new(gtk::Orientation::Vertical, 0);
+    conflicts_box.set_margin_start(20);
+    conflicts_box.set_margin_end(20);
+    conflicts_box.set_margin_top(6);
+    
+    let list_page = gtk::Box::new(gtk::Orientation::Vertical, 0);
+    list_page.append(&conflicts_box);
+    list_page.append(&scrolled_window);
+    
+    // The keyboard map is shown instead of the list when toggled
+    let keyboard = keyboard::KeyboardView::new();
+    let stack = gtk::Stack::new();
+    stack.set_vexpand(true);
+    stack.add_named(&list_page, Some("list"));
+    stack.add_named(&keyboard.widget, Some("keyboard"));
+    main_box.append(&stack);
+    
+    keyboard_button.connect_toggled(move |button| {
+        stack.set_visible_child_name(if button.is_active() { "keyboard" } else { "list" });
+    });
+    
+    let state = Rc::new(AppState {
+        window: window.clone(),
+        scrolled_window,
+        sections_box,
+        conflicts_box,
+        search_entry: search_entry.clone(),
+        live_button: live_button.clone(),
+        leaving_live_mode: Cell::new(false),
+        removed_button: removed_button.clone(),
+        capture_button: capture_button.clone(),
+        capture_label,
+        keyboard,
+        config: config.clone(),
+        keybinds: RefCell::new(Vec::new()),
+        config_files: RefCell::new(Vec::new()),
+        config_paths: RefCell::new(Vec::new()),
+        variables: RefCell::new(HashMap::new()),
+        conflicts: RefCell::new(Vec::new()),
+        source_warnings: RefCell::new(Vec::new()),
+        gestures: RefCell::new(Vec::new()),
+        sections: RefCell::new(Vec::new()),
+        displayed: RefCell::new(Vec::new()),
+        captured: RefCell::new(None),
+        watcher: watcher::ConfigWatcher::new(),
+    });
+    
+    // Parse keybinds from config
+    state.reload();
     
     // Set up simple search filtering
-    let keybind_flow_weak = keybind_flow.downgrade();
-    search_entry.connect_search_changed(move |entry| {
-        let search_text = entry.text().to_lowercase();
-        
-        if let Some(flow) = keybind_flow_weak.upgrade() {
-            // Get all children safely
-            for child in flow.observe_children().snapshot().iter() {
-                if let Some(flow_child) = child.downcast_ref::<gtk::FlowBoxChild>() {
-                    // Default visibility - show all if search is empty
-                    let mut visible = search_text.is_empty();
-                    
-                    // Only do detailed check if needed
-                    if !search_text.is_empty() {
-                        if let Some(frame) = flow_child.child() {
-                            if let Some(box_widget) = frame.first_child() {
-                                // Try each label in the box for a match
-                                if let Some(keybind_box) = box_widget.downcast_ref::<gtk::Box>() {
-                                    let mut curr = keybind_box.first_child();
-                                    while let Some(widget) = curr {
-                                        if let Some(label) = widget.downcast_ref::<gtk::Label>() {
-                                            if label.text().to_lowercase().contains(&search_text) {
-                                                visible = true;
-                                                break;
-                                            }
-                                        }
-                                        curr = widget.next_sibling();
-                                    }
-                                }
-                            }
-                        }
-                    }
-                    
-                    // Set visibility based on search match
-                    flow_child.set_visible(visible);
-                }
+    let state_weak = Rc::downgrade(&state);
+    search_entry.connect_search_changed(move |_| {
+        if let Some(state) = state_weak.upgrade() {
+            state.apply_filter();
+        }
+    });
+    
+    // Export the parsed keybinds as PDF, SVG or PNG
+    let state_weak = Rc::downgrade(&state);
+    export_button.connect_clicked(move |_| {
+        if let Some(state) = state_weak.upgrade() {
+            let effective = state.keybinds.borrow().iter().filter(|keybind| keybind.removed_by.is_none()).cloned().collect();
+            show_export_dialog(&state.window, effective);
+        }
+    });
+    
+    // Add a new keybind to one of the config files
+    let state_weak = Rc::downgrade(&state);
+    add_button.connect_clicked(move |_| {
+        if let Some(state) = state_weak.upgrade() {
+            state.add_keybind();
+        }
+    });
+    
+    // Switch between the config files and the live comparison
+    let state_weak = Rc::downgrade(&state);
+    live_button.connect_toggled(move |_| {
+        if let Some(state) = state_weak.upgrade() {
+            // Falling back from a failed live read already refreshes
+            if state.leaving_live_mode.get() {
+                return;
             }
+            state.refresh();
+        }
+    });
+    
+    // Show or hide the binds removed by unbind
+    let state_weak = Rc::downgrade(&state);
+    removed_button.connect_toggled(move |_| {
+        if let Some(state) = state_weak.upgrade() {
+            state.apply_filter();
         }
     });
     
+    // Grab key combinations before any widget sees them while capturing
+    let key_controller = gtk::EventControllerKey::new();
+    key_controller.set_propagation_phase(gtk::PropagationPhase::Capture);
+    let state_weak = Rc::downgrade(&state);
+    key_controller.connect_key_pressed(move |controller, keyval, keycode, modifiers| {
+        match state_weak.upgrade() {
+            Some(state) if state.capture_key(keyval, keycode, controller.group(), modifiers) => gtk::glib::Propagation::Stop,
+            _ => gtk::glib::Propagation::Proceed,
+        }
+    });
+    window.add_controller(key_controller);
+    
+    let state_weak = Rc::downgrade(&state);
+    capture_button.connect_toggled(move |button| {
+        if let Some(state) = state_weak.upgrade() {
+            state.set_capturing(button.is_active());
+        }
+    });
+    
+    // Keep the state alive as long as the window
+    window.connect_destroy(move |_| {
+        let _ = &state;
+    });
+    
     // Set the main box as the window's child
     window.set_child(Some(&main_box));
     
@@ -297,48 +384,49 @@ fn build_ui(app: &Application) {
     window.present();
 }
 
+/// Ask for a file name and export the cheat sheet to it
+fn show_export_dialog(window: &ApplicationWindow, keybinds: Vec<config_parser::Keybind>) {
+    let dialog = gtk::FileChooserNative::new(
+        Some("Export cheat sheet"),
+        Some(window),
+        gtk::FileChooserAction::Save,
+        Some("Export"),
+        Some("Cancel"),
+    );
+    dialog.set_current_name("keybinds.pdf");
+    
+    for (name, pattern) in [("PDF document", "*.pdf"), ("SVG image", "*.svg"), ("PNG image", "*.png")] {
+        let filter = gtk::FileFilter::new();
+        filter.set_name(Some(name));
+        filter.add_pattern(pattern);
+        dialog.add_filter(&filter);
+    }
+    
+    let window = window.clone();
+    dialog.connect_response(move |dialog, response| {
+        if response == gtk::ResponseType::Accept {
+            if let Some(mut path) = dialog.file().and_then(|file| file.path()) {
+                // Default to PDF when no extension was typed
+                if path.extension().is_none() {
+                    path.set_extension("pdf");
+                }
+                
+                if let Err(e) = cheatsheet::export_cheat_sheet(&keybinds, &path) {
+                    error!("Failed to export cheat sheet: {:#}", e);
+                    ui::show_error(&window, "Failed to export cheat sheet", &format!("{:#}", e));
+                }
+            }
+        }
+        dialog.destroy();
+    });
+    
+    dialog.show();
+}
+
 fn load_css() {
     // Load CSS for styling
     let provider = CssProvider::new();
-    provider.load_from_data(
-        "
-        .header {
-            padding: 10px 0;
-        }
-        
-        .header-title {
-            font-size: 16px;
-            font-weight: normal;
-        }
-        
-        .header-subtitle {
-            font-size: 24px;
-            font-weight: bold;
-        }
-        
-        .search-button {
-            border-radius: 18px;
-            padding: 5px 15px;
-            font-weight: bold;
-        }
-        
-        .keybind-combo {
-            font-weight: bold;
-            font-size: 15px;
-            margin-bottom: 3px;
-        }
-        
-        .keybind-action {
-            font-size: 12px;
-            opacity: 0.7;
-        }
-        
-        entry {
-            border-radius: 4px;
-            padding: 6px;
-        }
-        "
-    );
+    provider.load_from_data(&theme::css());
     
     // Add the provider to the default screen
     gtk::style_context_add_provider_for_display(
@@ -0,0 +1,83 @@
+/// Modifier bits used by Hyprland in the `modmask` field, in display order
+const MODIFIER_MASKS: &[(&str, u32)] = &[
+    ("SUPER", 1 << 6),
+    ("CTRL", 1 << 2),
+    ("ALT", 1 << 3),
+    ("SHIFT", 1 << 0),
+    ("CAPS", 1 << 1),
+    ("MOD2", 1 << 4),
+    ("MOD3", 1 << 5),
+    ("MOD5", 1 << 7),
+];
+
+/// Compute the modmask for modifier names the same way Hyprland does,
+/// by looking for known names anywhere in the string (so `SUPER_SHIFT` works)
+pub fn modmask_from_modifiers(modifiers: &[String]) -> u32 {
+    let text = modifiers.join(" ").to_uppercase();
+    let mut modmask = 0;
+    
+    if text.contains("SHIFT") { modmask |= 1 << 0; }
+    if text.contains("CAPS") { modmask |= 1 << 1; }
+    if text.contains("CTRL") || text.contains("CONTROL") { modmask |= 1 << 2; }
+    if text.contains("ALT") || text.contains("MOD1") { modmask |= 1 << 3; }
+    if text.contains("MOD2") { modmask |= 1 << 4; }
+    if text.contains("MOD3") { modmask |= 1 << 5; }
+    if text.contains("SUPER") || text.contains("WIN") || text.contains("LOGO") || text.contains("MOD4") || text.contains("META") {
+        modmask |= 1 << 6;
+    }
+    if text.contains("MOD5") { modmask |= 1 << 7; }
+    
+    modmask
+}
+
+/// Check whether `name` is a modifier name Hyprland knows, e.g. `super` or `mod4`
+pub fn is_modifier_name(name: &str) -> bool {
+    const NAMES: &[&str] = &[
+        "SHIFT", "CAPS", "CTRL", "CONTROL", "ALT", "MOD1", "MOD2", "MOD3", "MOD4", "MOD5", "SUPER", "WIN", "LOGO", "META",
+    ];
+    NAMES.contains(&name.to_uppercase().as_str())
+}
+
+/// Turn a Hyprland modmask into canonical modifier names, e.g. `65` into `SUPER SHIFT`
+pub fn modifiers_from_mask(modmask: u32) -> Vec<String> {
+    MODIFIER_MASKS
+        .iter()
+        .filter(|(_, mask)| modmask & mask != 0)
+        .map(|(name, _)| name.to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn modmask(modifiers: &str) -> u32 {
+        modmask_from_modifiers(&modifiers.split_whitespace().map(str::to_string).collect::<Vec<_>>())
+    }
+    
+    #[test]
+    fn aliases_share_a_mask() {
+        assert_eq!(modmask("SUPER"), 64);
+        assert_eq!(modmask("MOD4"), 64);
+        assert_eq!(modmask("WIN"), 64);
+        assert_eq!(modmask("ALT"), 8);
+        assert_eq!(modmask("MOD1"), 8);
+        assert_eq!(modmask("CONTROL"), modmask("CTRL"));
+        assert_eq!(modmask("mod4 shift"), modmask("SUPER_SHIFT"));
+        assert_eq!(modmask(""), 0);
+    }
+    
+    #[test]
+    fn masks_turn_into_canonical_names() {
+        assert_eq!(modifiers_from_mask(modmask("MOD1 MOD4 SHIFT")), vec!["SUPER", "ALT", "SHIFT"]);
+        assert!(modifiers_from_mask(0).is_empty());
+    }
+    
+    #[test]
+    fn only_whole_names_are_modifier_names() {
+        assert!(is_modifier_name("super"));
+        assert!(is_modifier_name("Mod1"));
+        assert!(!is_modifier_name("SUPER_SHIFT"));
+        assert!(!is_modifier_name("e"));
+    }
+}
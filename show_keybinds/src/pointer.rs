@@ -0,0 +1,209 @@
+//! Knowledge about mouse buttons and touchpad gestures
+
+use crate::config_parser::Origin;
+use crate::dispatchers;
+
+/// A mouse button or scroll direction used as the key of a bind
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MouseInput {
+    pub name: &'static str,
+    pub icon: &'static str,
+}
+
+/// Find the mouse input of a bind key such as `mouse:272` or `mouse_down`.
+/// Returns `None` for keyboard keys.
+pub fn mouse_input(key: &str) -> Option<MouseInput> {
+    let key = key.trim().to_lowercase();
+    
+    // Button codes are the Linux input event codes (BTN_LEFT and so on)
+    let (name, icon) = match key.as_str() {
+        "mouse:272" => ("Left button", "input-mouse-symbolic"),
+        "mouse:273" => ("Right button", "input-mouse-symbolic"),
+        "mouse:274" => ("Middle button", "input-mouse-symbolic"),
+        "mouse:275" => ("Back button", "go-previous-symbolic"),
+        "mouse:276" => ("Forward button", "go-next-symbolic"),
+        "mouse_up" => ("Scroll up", "go-up-symbolic"),
+        "mouse_down" => ("Scroll down", "go-down-symbolic"),
+        "mouse_left" => ("Scroll left", "go-previous-symbolic"),
+        "mouse_right" => ("Scroll right", "go-next-symbolic"),
+        _ if key.starts_with("mouse:") => ("Mouse button", "input-mouse-symbolic"),
+        _ => return None,
+    };
+    
+    Some(MouseInput { name, icon })
+}
+
+/// A touchpad gesture, from the `gestures { }` block or a `gesture =` line
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gesture {
+    /// The movement, e.g. `3-finger horizontal swipe`
+    pub input: String,
+    /// What the gesture does in plain words
+    pub action: String,
+    /// Options that change how the gesture behaves, e.g. `inverted`
+    pub details: Vec<String>,
+    pub origin: Origin,
+}
+
+/// Icon shown next to gestures
+pub const GESTURE_ICON: &str = "input-touchpad-symbolic";
+
+/// Check a boolean option value the way Hyprland reads it
+fn is_enabled(value: &str) -> bool {
+    matches!(value.trim().to_lowercase().as_str(), "true" | "yes" | "on" | "1")
+}
+
+/// Build the workspace swipe gesture from the options of the `gestures`
+/// section, given as (name without `gestures:`, value, origin) in config order.
+/// Returns `None` when workspace swipe is not enabled.
+pub fn workspace_swipe(options: &[(String, String, Origin)]) -> Option<Gesture> {
+    // Later assignments override earlier ones
+    let value = |name: &str| {
+        options
+            .iter()
+            .rev()
+            .find(|(option, _, _)| option == name)
+            .map(|(_, value, origin)| (value.trim(), origin))
+    };
+    
+    let (enabled, origin) = value("workspace_swipe")?;
+    if !is_enabled(enabled) {
+        return None;
+    }
+    
+    let fingers = value("workspace_swipe_fingers").map(|(fingers, _)| fingers).unwrap_or("3");
+    let min_fingers = value("workspace_swipe_min_fingers").is_some_and(|(min, _)| is_enabled(min));
+    
+    let mut details = Vec::new();
+    if value("workspace_swipe_invert").is_some_and(|(invert, _)| !is_enabled(invert)) {
+        details.push("not inverted".to_string());
+    }
+    if let Some((distance, _)) = value("workspace_swipe_distance") {
+        details.push(format!("{}px per workspace", distance));
+    }
+    if value("workspace_swipe_create_new").is_some_and(|(create, _)| !is_enabled(create)) {
+        details.push("no new workspaces".to_string());
+    }
+    if value("workspace_swipe_forever").is_some_and(|(forever, _)| is_enabled(forever)) {
+        details.push("swipes past neighbours".to_string());
+    }
+    
+    Some(Gesture {
+        input: format!("{}{}-finger horizontal swipe", fingers, if min_fingers { "+" } else { "" }),
+        action: "Switch workspace".to_string(),
+        details,
+        origin: origin.clone(),
+    })
+}
+
+/// Parse a `gesture = FINGERS, DIRECTION, [OPTIONS,] ACTION[, ARGS]` line,
+/// e.g. `gesture = 3, down, mod: ALT, close`
+pub fn parse_gesture_line(value: &str, origin: Origin) -> Option<Gesture> {
+    let parts: Vec<&str> = value.split(',').map(str::trim).collect();
+    let fingers: u32 = parts.first()?.parse().ok()?;
+    let direction = parts.get(1)?.to_lowercase();
+    
+    // `mod:` and `scale:` options come before the action
+    let rest = parts.get(2..)?;
+    let option_count = rest.iter().take_while(|part| part.starts_with("mod:") || part.starts_with("scale:")).count();
+    let details: Vec<String> = rest[..option_count].iter().map(|part| part.replace(": ", " ")).collect();
+    let action = *rest.get(option_count)?;
+    let args = rest[option_count + 1..].join(", ");
+    
+    let input = match direction.as_str() {
+        "swipe" => format!("{}-finger swipe", fingers),
+        "pinch" => format!("{}-finger pinch", fingers),
+        "pinchin" => format!("{}-finger pinch in", fingers),
+        "pinchout" => format!("{}-finger pinch out", fingers),
+        _ => format!("{}-finger {} swipe", fingers, direction),
+    };
+    
+    let action = match action.to_lowercase().as_str() {
+        "workspace" => "Switch workspace".to_string(),
+        "move" => "Move window".to_string(),
+        "resize" => "Resize window".to_string(),
+        "close" => "Close window".to_string(),
+        "fullscreen" => "Toggle fullscreen".to_string(),
+        "float" => "Toggle floating".to_string(),
+        "special" => format!("Toggle special workspace {}", args),
+        "dispatcher" => {
+            let (dispatcher, dispatcher_args) = args.split_once(',').unwrap_or((&args, ""));
+            dispatchers::describe(dispatcher.trim(), dispatcher_args).unwrap_or_else(|| args.clone())
+        }
+        _ => format!("{} {}", action, args),
+    };
+    
+    Some(Gesture {
+        input,
+        action: action.trim_end().to_string(),
+        details,
+        origin,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn origin(line: usize) -> Origin {
+        Origin { file: "hyprland.conf".into(), line, text: String::new() }
+    }
+    
+    fn name(key: &str) -> Option<&'static str> {
+        mouse_input(key).map(|input| input.name)
+    }
+    
+    #[test]
+    fn mouse_keys_have_names() {
+        assert_eq!(name("mouse:272"), Some("Left button"));
+        assert_eq!(name("mouse:273"), Some("Right button"));
+        assert_eq!(name("MOUSE_DOWN"), Some("Scroll down"));
+        // Buttons without a name of their own still count as mouse input
+        assert_eq!(name("mouse:280"), Some("Mouse button"));
+        assert_eq!(name("Q"), None);
+        assert_eq!(name("mouse"), None);
+    }
+    
+    #[test]
+    fn gesture_lines_are_described() {
+        let gesture = parse_gesture_line("3, down, mod: ALT, close", origin(4)).unwrap();
+        assert_eq!(gesture.input, "3-finger down swipe");
+        assert_eq!(gesture.action, "Close window");
+        assert_eq!(gesture.details, vec!["mod ALT"]);
+        
+        let gesture = parse_gesture_line("4, pinchin, dispatcher, workspace, e+1", origin(5)).unwrap();
+        assert_eq!(gesture.input, "4-finger pinch in");
+        assert_eq!(gesture.action, "Switch to the next workspace");
+        
+        // Unknown actions are shown as written
+        let gesture = parse_gesture_line("3, up, unknownaction, arg", origin(6)).unwrap();
+        assert_eq!(gesture.action, "unknownaction arg");
+        
+        assert_eq!(parse_gesture_line("three, up, close", origin(7)), None);
+    }
+    
+    #[test]
+    fn workspace_swipe_follows_the_last_assignment() {
+        let options = |entries: &[(&str, &str)]| -> Vec<(String, String, Origin)> {
+            entries
+                .iter()
+                .enumerate()
+                .map(|(idx, (name, value))| (name.to_string(), value.to_string(), origin(idx + 1)))
+                .collect()
+        };
+        
+        let gesture = workspace_swipe(&options(&[
+            ("workspace_swipe", "false"),
+            ("workspace_swipe", "yes"),
+            ("workspace_swipe_fingers", "4"),
+            ("workspace_swipe_invert", "false"),
+        ]))
+        .unwrap();
+        assert_eq!(gesture.input, "4-finger horizontal swipe");
+        assert_eq!(gesture.details, vec!["not inverted"]);
+        assert_eq!(gesture.origin.line, 2);
+        
+        assert_eq!(workspace_swipe(&options(&[("workspace_swipe", "off")])), None);
+        assert_eq!(workspace_swipe(&[]), None);
+    }
+}
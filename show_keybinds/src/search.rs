@@ -0,0 +1,275 @@
+use std::collections::HashMap;
+
+use crate::config_parser::Keybind;
+use crate::modifiers::{is_modifier_name, modmask_from_modifiers};
+
+/// A key combination typed as `super+shift+q`
+#[derive(Debug, Clone, PartialEq)]
+struct ComboFilter {
+    modmask: u32,
+    /// `None` when only modifiers were typed, e.g. `super+shift+`
+    key: Option<String>,
+}
+
+/// A parsed search query.
+///
+/// Words made of modifiers joined by `+`, optionally ending in a key, are
+/// key combinations, matched by modifier set in any order. `dispatcher:`, `file:` and `submap:` words filter on those
+/// fields. Everything else is free text that is fuzzy-matched against the
+/// combo, description and action.
+#[derive(Debug, Clone, Default)]
+pub struct Query {
+    combos: Vec<ComboFilter>,
+    dispatchers: Vec<String>,
+    files: Vec<String>,
+    submaps: Vec<String>,
+    terms: Vec<String>,
+}
+
+impl Query {
+    /// Parse the search text. `variables` resolves names such as `$mainMod`
+    /// in key combinations.
+    pub fn parse(text: &str, variables: &HashMap<String, String>) -> Self {
+        let mut query = Query::default();
+        
+        for word in text.split_whitespace() {
+            let lower = word.to_lowercase();
+            if let Some(value) = lower.strip_prefix("dispatcher:") {
+                query.dispatchers.push(value.to_string());
+            } else if let Some(value) = lower.strip_prefix("file:") {
+                query.files.push(value.to_string());
+            } else if let Some(value) = lower.strip_prefix("submap:") {
+                query.submaps.push(value.to_string());
+            } else if is_combo(word, variables) {
+                query.combos.push(parse_combo(word, variables));
+            } else {
+                query.terms.push(lower);
+            }
+        }
+        
+        query
+    }
+    
+    /// Free-text words, used to highlight matches
+    pub fn terms(&self) -> &[String] {
+        &self.terms
+    }
+    
+    /// Check a keybind against the query. Returns `None` if it does not
+    /// match, otherwise a score where higher is a better match.
+    pub fn score(&self, keybind: &Keybind) -> Option<i64> {
+        let modmask = modmask_from_modifiers(&keybind.modifiers);
+        let combos_match = self.combos.iter().all(|combo| {
+            combo.modmask == modmask
+                && combo.key.as_ref().is_none_or(|key| key.eq_ignore_ascii_case(&keybind.key))
+        });
+        if !combos_match {
+            return None;
+        }
+        
+        let dispatcher = keybind.dispatcher().to_lowercase();
+        if !self.dispatchers.iter().all(|value| dispatcher.contains(value.as_str())) {
+            return None;
+        }
+        
+        let file = keybind
+            .origin
+            .as_ref()
+            .map(|origin| origin.file.display().to_string().to_lowercase())
+            .unwrap_or_default();
+        if !self.files.iter().all(|value| file.contains(value.as_str())) {
+            return None;
+        }
+        
+        let submap = keybind.submap.as_deref().unwrap_or("").to_lowercase();
+        if !self.submaps.iter().all(|value| submap.contains(value.as_str())) {
+            return None;
+        }
+        
+        // Every word has to match one of the texts, the best match counts
+        let texts = [keybind.combo_text(), keybind.display_combo(), keybind.summary(), keybind.action.clone()];
+        let mut score = 0;
+        for term in &self.terms {
+            score += texts
+                .iter()
+                .filter_map(|text| fuzzy_match(term, text).map(|m| m.score))
+                .max()?;
+        }
+        
+        Some(score)
+    }
+    
+    /// Check plain text that has no combo, dispatcher or file, such as a
+    /// gesture, against the query. Any filter other than free text excludes it.
+    pub fn score_text(&self, text: &str) -> Option<i64> {
+        if !self.combos.is_empty() || !self.dispatchers.is_empty() || !self.files.is_empty() || !self.submaps.is_empty() {
+            return None;
+        }
+        
+        let mut score = 0;
+        for term in &self.terms {
+            score += fuzzy_match(term, text)?.score;
+        }
+        
+        Some(score)
+    }
+}
+
+/// Resolve a `$variable` part of a key combination
+fn resolve_variable(part: &str, variables: &HashMap<String, String>) -> String {
+    match part.strip_prefix('$') {
+        Some(name) => variables.get(name).cloned().unwrap_or_else(|| part.to_string()),
+        None => part.to_string(),
+    }
+}
+
+/// Check whether a word is a key combination: everything before the last `+`
+/// must be modifier names, so words like `e+1` or `c++` stay free text
+fn is_combo(word: &str, variables: &HashMap<String, String>) -> bool {
+    let Some((modifiers, _)) = word.rsplit_once('+') else {
+        return false;
+    };
+    !modifiers.is_empty() && modifiers.split('+').all(|part| {
+        let part = resolve_variable(part, variables);
+        !part.trim().is_empty() && part.split_whitespace().all(is_modifier_name)
+    })
+}
+
+/// Split `super+shift+q` into modifiers and key. The last part is the key
+/// unless it is a modifier itself.
+fn parse_combo(word: &str, variables: &HashMap<String, String>) -> ComboFilter {
+    let mut parts: Vec<String> = word
+        .split('+')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .map(|part| resolve_variable(part, variables))
+        .collect();
+    
+    let key = match parts.last() {
+        Some(last) if modmask_from_modifiers(std::slice::from_ref(last)) == 0 => parts.pop(),
+        _ => None,
+    };
+    
+    ComboFilter {
+        modmask: modmask_from_modifiers(&parts),
+        key,
+    }
+}
+
+/// Result of a fuzzy match
+#[derive(Debug, Clone, PartialEq)]
+pub struct FuzzyMatch {
+    pub score: i64,
+    /// Character positions in the text that matched
+    pub positions: Vec<usize>,
+}
+
+/// Match the characters of `pattern` in order (case-insensitively) anywhere
+/// in `text`. Substrings rank above scattered characters, and matches at
+/// the start of words rank higher.
+pub fn fuzzy_match(pattern: &str, text: &str) -> Option<FuzzyMatch> {
+    let pattern: Vec<char> = pattern.chars().flat_map(char::to_lowercase).collect();
+    let text: Vec<char> = text.chars().map(|c| c.to_lowercase().next().unwrap_or(c)).collect();
+    if pattern.is_empty() {
+        return Some(FuzzyMatch { score: 0, positions: Vec::new() });
+    }
+    
+    let word_start = |idx: usize| idx == 0 || !text[idx - 1].is_alphanumeric();
+    
+    // A plain substring is the best kind of match
+    if let Some(start) = text.windows(pattern.len()).position(|window| window == pattern.as_slice()) {
+        let mut score = 100 + 10 * pattern.len() as i64 - start.min(50) as i64;
+        if word_start(start) {
+            score += 30;
+        }
+        return Some(FuzzyMatch { score, positions: (start..start + pattern.len()).collect() });
+    }
+    
+    // Otherwise pick characters in order, rewarding runs and word starts
+    let mut positions = Vec::with_capacity(pattern.len());
+    let mut score = 0;
+    let mut next = 0;
+    for &wanted in &pattern {
+        let idx = (next..text.len()).find(|&idx| text[idx] == wanted)?;
+        score += 1;
+        if word_start(idx) {
+            score += 8;
+        }
+        if positions.last().is_some_and(|&last| last + 1 == idx) {
+            score += 5;
+        }
+        positions.push(idx);
+        next = idx + 1;
+    }
+    
+    Some(FuzzyMatch { score, positions })
+}
+
+/// Pango markup of `text` with the characters matched by any of the terms in bold
+pub fn highlight_markup(text: &str, terms: &[String]) -> String {
+    let mut highlighted = vec![false; text.chars().count()];
+    for term in terms {
+        if let Some(found) = fuzzy_match(term, text) {
+            for idx in found.positions {
+                highlighted[idx] = true;
+            }
+        }
+    }
+    
+    let mut markup = String::new();
+    let mut open = false;
+    for (c, highlight) in text.chars().zip(highlighted) {
+        if highlight != open {
+            markup.push_str(if highlight { "<span weight=\"bold\" underline=\"single\">" } else { "</span>" });
+            open = highlight;
+        }
+        markup.push_str(&gtk::glib::markup_escape_text(&c.to_string()));
+    }
+    if open {
+        markup.push_str("</span>");
+    }
+    
+    markup
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn parse(text: &str) -> Query {
+        let variables = HashMap::from([("mainMod".to_string(), "SUPER".to_string())]);
+        Query::parse(text, &variables)
+    }
+    
+    fn combo(modmask: u32, key: Option<&str>) -> ComboFilter {
+        ComboFilter { modmask, key: key.map(str::to_string) }
+    }
+    
+    #[test]
+    fn combos_are_modifiers_joined_by_plus() {
+        let query = parse("super+shift+q ALT+ $mainMod+Return ctrl+super");
+        assert_eq!(query.combos, vec![
+            combo(65, Some("q")),
+            combo(8, None),
+            combo(64, Some("Return")),
+            combo(68, None),
+        ]);
+        assert!(query.terms.is_empty());
+    }
+    
+    #[test]
+    fn words_with_plus_that_are_not_combos_are_free_text() {
+        let query = parse("e+1 c++ +q $unknown+q super++");
+        assert!(query.combos.is_empty());
+        assert_eq!(query.terms, vec!["e+1", "c++", "+q", "$unknown+q", "super++"]);
+    }
+    
+    #[test]
+    fn field_filters_are_split_off() {
+        let query = parse("Dispatcher:exec file:apps submap:resize Firefox");
+        assert_eq!(query.dispatchers, vec!["exec"]);
+        assert_eq!(query.files, vec!["apps"]);
+        assert_eq!(query.submaps, vec!["resize"]);
+        assert_eq!(query.terms, vec!["firefox"]);
+    }
+}
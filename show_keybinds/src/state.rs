@@ -0,0 +1,336 @@
+use gtk::prelude::*;
+use gtk::{ApplicationWindow, Label, ScrolledWindow, SearchEntry, ToggleButton};
+use log::{error, info};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use crate::capture::CapturedCombo;
+use crate::config_parser::{ConfigParser, Keybind, SourceWarning};
+use crate::conflicts::{self, Conflict};
+use crate::editor;
+use crate::ipc::{self, HyprlandIpc};
+use crate::keyboard::KeyboardView;
+use crate::pointer::Gesture;
+use crate::search::Query;
+use crate::ui::{self, CardActions, KeybindSection};
+use crate::watcher::ConfigWatcher;
+
+/// Shared state of the keybinds window
+pub struct AppState {
+    pub window: ApplicationWindow,
+    pub scrolled_window: ScrolledWindow,
+    /// Holds the collapsible keybind sections
+    pub sections_box: gtk::Box,
+    /// Holds the conflicts panel, empty when there are no conflicts
+    pub conflicts_box: gtk::Box,
+    pub search_entry: SearchEntry,
+    pub live_button: ToggleButton,
+    /// Set while live mode is switched off from code, so the toggle handler does not refresh again
+    pub leaving_live_mode: Cell<bool>,
+    /// Also show the binds removed by `unbind`
+    pub removed_button: ToggleButton,
+    pub capture_button: ToggleButton,
+    /// Shows the captured combo and whether it is bound
+    pub capture_label: Label,
+    pub keyboard: Rc<KeyboardView>,
+    /// Config file given with `--config`, discovered when unset
+    pub config: Option<PathBuf>,
+    /// Keybinds parsed from the config files
+    pub keybinds: RefCell<Vec<Keybind>>,
+    /// Config files read while parsing
+    pub config_files: RefCell<Vec<PathBuf>>,
+    /// Paths tried to find the main config file
+    pub config_paths: RefCell<Vec<PathBuf>>,
+    /// Config variables, used to resolve `$mainMod` in search queries
+    pub variables: RefCell<HashMap<String, String>>,
+    pub conflicts: RefCell<Vec<Conflict>>,
+    /// `source =` lines that did not resolve to a file
+    pub source_warnings: RefCell<Vec<SourceWarning>>,
+    /// Touchpad gestures, shown with the mouse binds
+    pub gestures: RefCell<Vec<Gesture>>,
+    pub sections: RefCell<Vec<KeybindSection>>,
+    /// Keybinds currently shown, the cards refer to indices into this list
+    pub displayed: RefCell<Vec<Keybind>>,
+    /// Last combo pressed in capture mode
+    pub captured: RefCell<Option<CapturedCombo>>,
+    /// Reloads the keybinds when a config file changes
+    pub watcher: ConfigWatcher,
+}
+
+impl AppState {
+    /// Parse the config files again and refresh the window
+    pub fn reload(self: &Rc<Self>) {
+        let mut config_parser = ConfigParser::new(self.config.as_deref());
+        let keybinds = match config_parser.parse_keybinds() {
+            Ok(keybinds) => {
+                info!("Found {} keybinds", keybinds.len());
+                keybinds
+            },
+            Err(e) => {
+                error!("Failed to parse keybinds: {}", e);
+                Vec::new()
+            }
+        };
+        
+        // Find binds that clash with each other
+        let conflicts = conflicts::detect_conflicts(&keybinds);
+        if !conflicts.is_empty() {
+            info!("Found {} keybind conflicts", conflicts.len());
+        }
+        
+        *self.keybinds.borrow_mut() = keybinds;
+        *self.config_files.borrow_mut() = config_parser.processed_files().to_vec();
+        *self.config_paths.borrow_mut() = config_parser.config_paths().to_vec();
+        *self.variables.borrow_mut() = config_parser.variables().clone();
+        *self.conflicts.borrow_mut() = conflicts;
+        *self.source_warnings.borrow_mut() = config_parser.source_warnings().to_vec();
+        *self.gestures.borrow_mut() = config_parser.gestures();
+        self.refresh();
+        
+        // Watch the files read this time, which picks up new `source =` lines,
+        // and the places where sourced files may still appear
+        let state = Rc::downgrade(self);
+        self.watcher.watch(config_parser.processed_files(), config_parser.source_patterns(), move || {
+            if let Some(state) = state.upgrade() {
+                info!("Config files changed, reloading keybinds");
+                state.reload_keeping_position();
+            }
+        });
+    }
+    
+    /// Reload after a config change without losing the scroll position.
+    /// The search text stays in the entry and is applied to the new list.
+    pub fn reload_keeping_position(self: &Rc<Self>) {
+        let adjustment = self.scrolled_window.vadjustment();
+        let position = adjustment.value();
+        self.reload();
+        
+        // The new list gets its size on the next layout
+        gtk::glib::idle_add_local_once(move || {
+            adjustment.set_value(position);
+        });
+    }
+    
+    /// The keybinds to show: the parsed ones, or the comparison with the
+    /// running compositor while live mode is active
+    fn displayed_keybinds(&self) -> Vec<Keybind> {
+        let keybinds = self.keybinds.borrow();
+        if !self.live_button.is_active() {
+            return keybinds.clone();
+        }
+        
+        match HyprlandIpc::from_env().and_then(|ipc| ipc.binds()) {
+            Ok(live_binds) => ipc::compare_with_live(&keybinds, &live_binds),
+            Err(e) => {
+                error!("Failed to read live keybinds: {:#}", e);
+                self.live_button.set_tooltip_text(Some(&format!("Failed to read live keybinds: {:#}", e)));
+                self.leaving_live_mode.set(true);
+                self.live_button.set_active(false);
+                self.leaving_live_mode.set(false);
+                keybinds.clone()
+            }
+        }
+    }
+    
+    /// Rebuild the keybind sections and the conflicts panel
+    pub fn refresh(self: &Rc<Self>) {
+        let displayed = self.displayed_keybinds();
+        
+        let sections = ui::rebuild_keybind_sections(&self.sections_box, &displayed, &self.gestures.borrow(), &self.card_actions());
+        *self.sections.borrow_mut() = sections;
+        // The keyboard shows the effective keymap only
+        let effective: Vec<Keybind> = displayed.iter().filter(|keybind| keybind.removed_by.is_none()).cloned().collect();
+        self.keyboard.update(&effective);
+        *self.displayed.borrow_mut() = displayed;
+        self.apply_filter();
+        
+        // Nothing was read, say where we looked
+        if self.config_files.borrow().is_empty() {
+            self.sections_box.append(&ui::create_empty_state(&self.config_paths.borrow()));
+        }
+        
+        // Show conflicts above the list, each one jumps to the conflicting cards
+        while let Some(child) = self.conflicts_box.first_child() {
+            self.conflicts_box.remove(&child);
+        }
+        let source_warnings = self.source_warnings.borrow();
+        if !source_warnings.is_empty() {
+            self.conflicts_box.append(&ui::create_source_warnings_panel(&source_warnings));
+        }
+        let conflicts = self.conflicts.borrow();
+        if !conflicts.is_empty() {
+            let state = Rc::downgrade(self);
+            let conflicts_panel = ui::create_conflicts_panel(&conflicts, &self.keybinds.borrow(), move |index| {
+                if let Some(state) = state.upgrade() {
+                    ui::scroll_to_keybind(&state.scrolled_window, &state.sections_box, &state.sections.borrow(), index);
+                }
+            });
+            self.conflicts_box.append(&conflicts_panel);
+        }
+    }
+    
+    /// Filter the cards by the captured combo in capture mode, otherwise by the search text
+    pub fn apply_filter(&self) {
+        let sections = self.sections.borrow();
+        if !self.capture_button.is_active() {
+            let query = Query::parse(&self.search_entry.text(), &self.variables.borrow());
+            ui::search_keybind_sections(&sections, &self.displayed.borrow(), &query, self.removed_button.is_active());
+            return;
+        }
+        
+        let captured = self.captured.borrow();
+        let Some(combo) = captured.as_ref() else {
+            self.capture_label.set_text("Press a key combination to find it, Esc to stop");
+            ui::filter_keybind_cards(&sections, |_, _| true);
+            return;
+        };
+        
+        // Only binds of the effective keymap are triggered by the combo
+        let displayed = self.displayed.borrow();
+        let show_removed = self.removed_button.is_active();
+        let active = |keybind: &Keybind| combo.matches(keybind) && keybind.removed_by.is_none();
+        let matching = displayed.iter().filter(|keybind| active(keybind)).count();
+        self.capture_label.set_text(&match matching {
+            0 => format!("{} is unbound", combo.text()),
+            1 => format!("{} is bound once", combo.text()),
+            n => format!("{} is bound {} times", combo.text(), n),
+        });
+        ui::filter_keybind_cards(&sections, |idx, _| {
+            displayed
+                .get(idx)
+                .is_some_and(|keybind| active(keybind) || (show_removed && combo.matches(keybind)))
+        });
+    }
+    
+    /// Start or stop capture mode after the capture button was toggled
+    pub fn set_capturing(&self, active: bool) {
+        *self.captured.borrow_mut() = None;
+        self.capture_label.set_visible(active);
+        if active {
+            self.window.grab_focus();
+        }
+        self.apply_filter();
+    }
+    
+    /// Handle a key press in capture mode, returns whether the key was consumed
+    pub fn capture_key(&self, keyval: gtk::gdk::Key, keycode: u32, group: u32, modifiers: gtk::gdk::ModifierType) -> bool {
+        if !self.capture_button.is_active() {
+            return false;
+        }
+        
+        if keyval == gtk::gdk::Key::Escape && !modifiers.intersects(gtk::gdk::MODIFIER_MASK) {
+            self.capture_button.set_active(false);
+            return true;
+        }
+        
+        if let Some(combo) = CapturedCombo::from_key_event(keyval, keycode, group, modifiers) {
+            info!("Captured {}", combo.text());
+            *self.captured.borrow_mut() = Some(combo);
+            self.apply_filter();
+        }
+        true
+    }
+    
+    /// Callbacks for the buttons on each keybind card
+    fn card_actions(self: &Rc<Self>) -> CardActions {
+        let edit_state = Rc::downgrade(self);
+        let delete_state = Rc::downgrade(self);
+        let run_state = Rc::downgrade(self);
+        
+        CardActions {
+            on_edit: Rc::new(move |index| {
+                if let Some(state) = edit_state.upgrade() {
+                    state.edit_keybind(index);
+                }
+            }),
+            on_delete: Rc::new(move |index| {
+                if let Some(state) = delete_state.upgrade() {
+                    state.delete_keybind(index);
+                }
+            }),
+            on_run: Rc::new(move |index| {
+                let state = run_state.upgrade().ok_or_else(|| anyhow::anyhow!("The window was closed"))?;
+                let keybind = state.displayed.borrow().get(index).cloned()
+                    .ok_or_else(|| anyhow::anyhow!("The keybind is no longer loaded"))?;
+                HyprlandIpc::from_env()?.dispatch_keybind(&keybind)
+            }),
+        }
+    }
+    
+    /// Open the editor for an existing keybind
+    pub fn edit_keybind(self: &Rc<Self>, index: usize) {
+        let Some(origin) = self.keybinds.borrow().get(index).and_then(|k| k.origin.clone()) else {
+            return;
+        };
+        
+        let state = Rc::downgrade(self);
+        let edited = origin.clone();
+        ui::edit_dialog::show_edit_dialog(&self.window, Some(&origin), &[], move |bind, _| {
+            editor::update_bind(&edited, bind)?;
+            if let Some(state) = state.upgrade() {
+                state.reload();
+            }
+            Ok(())
+        });
+    }
+    
+    /// Open the editor for a new keybind
+    pub fn add_keybind(self: &Rc<Self>) {
+        let files = self.config_files.borrow().clone();
+        let state = Rc::downgrade(self);
+        ui::edit_dialog::show_edit_dialog(&self.window, None, &files, move |bind, file| {
+            let file = file.ok_or_else(|| anyhow::anyhow!("No config file selected"))?;
+            editor::add_bind(file, bind)?;
+            if let Some(state) = state.upgrade() {
+                state.reload();
+            }
+            Ok(())
+        });
+    }
+    
+    /// Ask for confirmation and delete a keybind from its config file
+    pub fn delete_keybind(self: &Rc<Self>, index: usize) {
+        let Some(keybind) = self.keybinds.borrow().get(index).cloned() else {
+            return;
+        };
+        let Some(origin) = keybind.origin.clone() else {
+            return;
+        };
+        
+        let dialog = gtk::MessageDialog::builder()
+            .transient_for(&self.window)
+            .modal(true)
+            .message_type(gtk::MessageType::Question)
+            .buttons(gtk::ButtonsType::None)
+            .text(format!("Delete {}?", keybind.combo_text()))
+            .secondary_text(format!("This removes line {} from {:?}. A backup is made first.", origin.line, origin.file))
+            .build();
+        dialog.add_button("Cancel", gtk::ResponseType::Cancel);
+        dialog.add_button("Delete", gtk::ResponseType::Accept);
+        if let Some(button) = dialog.widget_for_response(gtk::ResponseType::Accept) {
+            button.add_css_class("destructive-action");
+        }
+        
+        let state = Rc::downgrade(self);
+        dialog.connect_response(move |dialog, response| {
+            dialog.close();
+            if response != gtk::ResponseType::Accept {
+                return;
+            }
+            let Some(state) = state.upgrade() else {
+                return;
+            };
+            
+            match editor::delete_bind(&origin) {
+                Ok(()) => state.reload(),
+                Err(e) => {
+                    error!("Failed to delete keybind: {:#}", e);
+                    ui::show_error(&state.window, "Failed to delete keybind", &format!("{:#}", e));
+                }
+            }
+        });
+        dialog.present();
+    }
+}
@@ -0,0 +1,211 @@
+//! Colours and font sizes shared by the window CSS and the exported cheat sheet
+
+use crate::dispatchers::DispatcherCategory;
+
+/// Accent colour used for section titles
+pub const ACCENT_COLOR: &str = "#89b4fa";
+/// Colour used for conflicts and other warnings
+pub const WARNING_COLOR: &str = "#f9e2af";
+
+pub const HEADER_TITLE_SIZE: f64 = 16.0;
+pub const HEADER_SUBTITLE_SIZE: f64 = 24.0;
+pub const SECTION_TITLE_SIZE: f64 = 15.0;
+pub const COMBO_SIZE: f64 = 15.0;
+pub const DESCRIPTION_SIZE: f64 = 13.0;
+pub const ACTION_SIZE: f64 = 12.0;
+/// Opacity of secondary text such as the raw action
+pub const SECONDARY_OPACITY: f64 = 0.7;
+
+/// Colour used for binds of a dispatcher category
+pub fn category_color(category: DispatcherCategory) -> &'static str {
+    match category {
+        DispatcherCategory::Workspaces => "#cba6f7",
+        DispatcherCategory::Windows => "#89b4fa",
+        DispatcherCategory::Launchers => "#a6e3a1",
+        DispatcherCategory::Media => "#fab387",
+        DispatcherCategory::System => "#f38ba8",
+        DispatcherCategory::Pointer => "#94e2d5",
+    }
+}
+
+/// Turn a `#rrggbb` colour into cairo RGB components
+pub fn rgb(hex: &str) -> (f64, f64, f64) {
+    let channel = |range: std::ops::Range<usize>| {
+        hex.trim_start_matches('#')
+            .get(range)
+            .and_then(|c| u8::from_str_radix(c, 16).ok())
+            .unwrap_or(0) as f64 / 255.0
+    };
+    
+    (channel(0..2), channel(2..4), channel(4..6))
+}
+
+/// CSS for the keybinds window
+pub fn css() -> String {
+    let mut css = format!(
+        "
+        .header {{
+            padding: 10px 0;
+        }}
+        
+        .header-title {{
+            font-size: {header_title}px;
+            font-weight: normal;
+        }}
+        
+        .header-subtitle {{
+            font-size: {header_subtitle}px;
+            font-weight: bold;
+        }}
+        
+        .search-button {{
+            border-radius: 18px;
+            padding: 5px 15px;
+            font-weight: bold;
+        }}
+        
+        .keybind-combo {{
+            font-weight: bold;
+            font-size: {combo}px;
+            margin-bottom: 3px;
+        }}
+        
+        .section-title {{
+            font-weight: bold;
+            font-size: {section_title}px;
+            color: {accent};
+        }}
+        
+        .section-subtitle {{
+            font-size: {action}px;
+            opacity: {secondary_opacity};
+        }}
+        
+        .conflicts-title {{
+            font-weight: bold;
+            color: {warning};
+        }}
+        
+        .keybind-highlight {{
+            border-color: {warning};
+            background-color: alpha({warning}, 0.15);
+        }}
+        
+        .keybind-description {{
+            font-size: {description}px;
+        }}
+        
+        .keybind-action {{
+            font-size: {action}px;
+            opacity: {secondary_opacity};
+        }}
+        
+        .keybind-badge {{
+            font-size: 10px;
+            padding: 1px 6px;
+            border-radius: 8px;
+            background-color: alpha(currentColor, 0.12);
+        }}
+        
+        .keybind-source-badge {{
+            font-size: 10px;
+            font-weight: bold;
+            padding: 1px 6px;
+            border-radius: 8px;
+            background-color: alpha({warning}, 0.3);
+        }}
+        
+        .empty-state {{
+            margin: 40px 20px;
+        }}
+        
+        .empty-state-path {{
+            font-family: monospace;
+            font-size: {action}px;
+        }}
+        
+        .keybind-removed {{
+            opacity: 0.6;
+        }}
+        
+        .keybind-removed .keybind-combo,
+        .keybind-removed .keybind-description,
+        .keybind-removed .keybind-action {{
+            text-decoration-line: line-through;
+        }}
+        
+        .keybind-removed-note {{
+            font-size: {action}px;
+            color: {warning};
+        }}
+        
+        .keybind-combo-icon {{
+            opacity: {secondary_opacity};
+        }}
+        
+        .keybind-card-button {{
+            min-height: 20px;
+            min-width: 20px;
+            padding: 2px;
+            opacity: {secondary_opacity};
+        }}
+        
+        .capture-status {{
+            font-weight: bold;
+            font-size: {description}px;
+            color: {accent};
+        }}
+        
+        .keyboard-key {{
+            font-size: {action}px;
+            padding: 10px 2px;
+            border-radius: 6px;
+            background-color: alpha(currentColor, 0.06);
+        }}
+        
+        .keyboard-key-modifier {{
+            font-weight: bold;
+            background-color: alpha({accent}, 0.35);
+        }}
+        
+        .keyboard-legend {{
+            font-size: {action}px;
+            padding: 1px 8px;
+            border-radius: 8px;
+        }}
+        
+        .edit-error {{
+            color: {warning};
+        }}
+        
+        entry {{
+            border-radius: 4px;
+            padding: 6px;
+        }}
+        ",
+        header_title = HEADER_TITLE_SIZE,
+        header_subtitle = HEADER_SUBTITLE_SIZE,
+        section_title = SECTION_TITLE_SIZE,
+        combo = COMBO_SIZE,
+        description = DESCRIPTION_SIZE,
+        action = ACTION_SIZE,
+        secondary_opacity = SECONDARY_OPACITY,
+        accent = ACCENT_COLOR,
+        warning = WARNING_COLOR,
+    );
+    
+    // Bound keys on the keyboard map are coloured by what they do
+    for category in DispatcherCategory::ALL {
+        css.push_str(&format!(
+            "
+        .keyboard-key-{name}, .keyboard-legend-{name} {{
+            background-color: alpha({color}, 0.55);
+        }}
+        ",
+            name = category.css_name(),
+            color = category_color(category),
+        ));
+    }
+    
+    css
+}
@@ -1,11 +1,23 @@
 use gtk::prelude::*;
 use gtk::{
-    Box as GtkBox, Label, ListBox, ListBoxRow, Orientation, ScrolledWindow, 
-    SearchEntry, SelectionMode,
+    Box as GtkBox, Expander, FlowBox, FlowBoxChild, Frame, Label, ListBox, ListBoxRow,
+    Orientation, ScrolledWindow, SearchEntry, SelectionMode,
 };
 use log::{debug, info};
 
-use crate::config_parser::Keybind;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::time::Duration;
+
+use crate::config_parser::{Keybind, SourceWarning};
+use crate::conflicts::Conflict;
+use crate::dispatchers::DispatcherCategory;
+use crate::pointer::{self, Gesture};
+use crate::search::{self, Query};
+
+pub mod edit_dialog;
 
 /// Create the main content box
 pub fn create_content_box() -> (GtkBox, ListBox) {
@@ -140,4 +152,633 @@ pub fn populate_keybinds(list_box: &ListBox, keybinds: &[Keybind]) {
     }
     
     info!("Finished populating the list box");
-} 
\ No newline at end of file
+}
+
+/// A collapsible group of keybinds that share the same submap
+#[derive(Clone)]
+pub struct KeybindSection {
+    pub expander: Expander,
+    pub flow: FlowBox,
+    /// Index into the keybind list and the card showing that keybind
+    pub cards: Vec<(usize, FlowBoxChild)>,
+    /// Gesture cards with the text they are searched by
+    pub gesture_cards: Vec<(String, FlowBoxChild)>,
+}
+
+/// Callbacks for the buttons of a keybind card, called with the keybind index
+#[derive(Clone)]
+pub struct CardActions {
+    pub on_edit: Rc<dyn Fn(usize)>,
+    pub on_delete: Rc<dyn Fn(usize)>,
+    /// Runs the bound action, the error is shown on the card
+    pub on_run: Rc<dyn Fn(usize) -> anyhow::Result<()>>,
+}
+
+/// Create the card widget shown for a single keybind
+pub fn create_keybind_card(keybind: &Keybind, index: usize, actions: &CardActions) -> Frame {
+    // Create a frame for better visibility
+    let frame = Frame::new(None);
+    frame.set_size_request(180, 80); // Sabit genişlik ve minimum yükseklik
+    
+    // Create a box for this keybind
+    let keybind_box = GtkBox::new(Orientation::Vertical, 3);
+    keybind_box.set_margin_top(8);
+    keybind_box.set_margin_bottom(8);
+    keybind_box.set_margin_start(10);
+    keybind_box.set_margin_end(10);
+    keybind_box.set_vexpand(true);  // Dikey genişleme
+    keybind_box.set_valign(gtk::Align::Center);  // Dikey ortalama
+    
+    // Create the key combo label, mouse buttons are shown by name
+    let key_label = Label::new(Some(&keybind.display_combo()));
+    key_label.add_css_class("keybind-combo");
+    key_label.set_xalign(0.0);
+    key_label.set_ellipsize(gtk::pango::EllipsizeMode::End);
+    
+    // Create the action label - simplify it for display
+    let display_action = if keybind.dispatcher() == "exec" {
+        match keybind.args().split_whitespace().next() {
+            Some(command) => command.to_string(),
+            None => keybind.action.clone(),
+        }
+    } else {
+        keybind.action.clone()
+    };
+    
+    let action_label = Label::new(Some(&display_action));
+    action_label.add_css_class("keybind-action");
+    action_label.set_xalign(0.0);
+    action_label.set_ellipsize(gtk::pango::EllipsizeMode::End);
+    
+    // Add the labels to the box - a description (or the action described
+    // in plain words) becomes the main text and the raw dispatcher is shown underneath it
+    match pointer::mouse_input(&keybind.key) {
+        Some(input) => keybind_box.append(&create_combo_row(input.icon, &key_label)),
+        None => keybind_box.append(&key_label),
+    }
+    if let Some(description) = keybind.description.clone().or_else(|| keybind.action_description()) {
+        let description_label = Label::new(Some(&description));
+        description_label.add_css_class("keybind-description");
+        description_label.set_xalign(0.0);
+        description_label.set_ellipsize(gtk::pango::EllipsizeMode::End);
+        keybind_box.append(&description_label);
+        
+        action_label.set_text(&keybind.action);
+        action_label.set_tooltip_text(Some(&keybind.action));
+    }
+    keybind_box.append(&action_label);
+    
+    // Binds removed by an unbind are struck through and say where they were removed
+    if let Some(unbind) = &keybind.removed_by {
+        frame.add_css_class("keybind-removed");
+        let removed_label = Label::new(Some(&format!("Removed by unbind at {}", unbind.display_short())));
+        removed_label.add_css_class("keybind-removed-note");
+        removed_label.set_xalign(0.0);
+        removed_label.set_ellipsize(gtk::pango::EllipsizeMode::End);
+        removed_label.set_tooltip_text(Some(&format!("{}:{}\n{}", unbind.file.display(), unbind.line, unbind.text.trim())));
+        keybind_box.append(&removed_label);
+    }
+    
+    // Show bind flags (repeat, locked, mouse...) and the live/file status as small badges
+    let badges = keybind.flags.badges();
+    let source_badge = keybind.source.badge();
+    if !badges.is_empty() || source_badge.is_some() {
+        let badge_box = GtkBox::new(Orientation::Horizontal, 4);
+        badge_box.set_margin_top(2);
+        if let Some(badge) = source_badge {
+            let badge_label = Label::new(Some(badge));
+            badge_label.add_css_class("keybind-source-badge");
+            badge_box.append(&badge_label);
+        }
+        for badge in badges {
+            let badge_label = Label::new(Some(badge));
+            badge_label.add_css_class("keybind-badge");
+            badge_box.append(&badge_label);
+        }
+        keybind_box.append(&badge_box);
+    }
+    
+    let actions_box = GtkBox::new(Orientation::Horizontal, 2);
+    let buttons_box = GtkBox::new(Orientation::Horizontal, 2);
+    buttons_box.set_halign(gtk::Align::End);
+    buttons_box.set_hexpand(true);
+    
+    // Errors of the run button are shown next to the buttons
+    let error_label = Label::new(None);
+    error_label.add_css_class("edit-error");
+    error_label.set_xalign(0.0);
+    error_label.set_ellipsize(gtk::pango::EllipsizeMode::End);
+    error_label.set_visible(false);
+    actions_box.append(&error_label);
+    actions_box.append(&buttons_box);
+    
+    // Mouse binds only make sense while dragging, and removed binds are not active
+    if !keybind.flags.mouse && keybind.removed_by.is_none() {
+        let run_button = gtk::Button::from_icon_name("media-playback-start-symbolic");
+        run_button.add_css_class("flat");
+        run_button.add_css_class("keybind-card-button");
+        run_button.set_tooltip_text(Some("Run this action now"));
+        let on_run = actions.on_run.clone();
+        run_button.connect_clicked(move |_| match on_run(index) {
+            Ok(()) => error_label.set_visible(false),
+            Err(e) => {
+                error_label.set_text(&format!("{:#}", e));
+                error_label.set_tooltip_text(Some(&format!("{:#}", e)));
+                error_label.set_visible(true);
+            }
+        });
+        buttons_box.append(&run_button);
+    }
+    
+    // Only binds read from a config file can be edited in place
+    if keybind.origin.is_some() {
+        let edit_button = gtk::Button::from_icon_name("document-edit-symbolic");
+        edit_button.add_css_class("flat");
+        edit_button.add_css_class("keybind-card-button");
+        edit_button.set_tooltip_text(Some("Edit keybind"));
+        let on_edit = actions.on_edit.clone();
+        edit_button.connect_clicked(move |_| on_edit(index));
+        
+        let delete_button = gtk::Button::from_icon_name("user-trash-symbolic");
+        delete_button.add_css_class("flat");
+        delete_button.add_css_class("keybind-card-button");
+        delete_button.set_tooltip_text(Some("Delete keybind"));
+        let on_delete = actions.on_delete.clone();
+        delete_button.connect_clicked(move |_| on_delete(index));
+        
+        buttons_box.append(&edit_button);
+        buttons_box.append(&delete_button);
+    }
+    if buttons_box.first_child().is_some() {
+        keybind_box.append(&actions_box);
+    }
+    
+    // Add the box to the frame
+    frame.set_child(Some(&keybind_box));
+    frame
+}
+
+/// Put an icon in front of a combo label
+fn create_combo_row(icon_name: &str, key_label: &Label) -> GtkBox {
+    let combo_row = GtkBox::new(Orientation::Horizontal, 6);
+    combo_row.add_css_class("keybind-combo-row");
+    
+    let icon = gtk::Image::from_icon_name(icon_name);
+    icon.add_css_class("keybind-combo-icon");
+    combo_row.append(&icon);
+    combo_row.append(key_label);
+    combo_row
+}
+
+/// Create the card widget shown for a touchpad gesture
+pub fn create_gesture_card(gesture: &Gesture) -> Frame {
+    let frame = Frame::new(None);
+    frame.set_size_request(180, 80);
+    frame.set_tooltip_text(Some(&gesture.origin.display_short()));
+    
+    let gesture_box = GtkBox::new(Orientation::Vertical, 3);
+    gesture_box.set_margin_top(8);
+    gesture_box.set_margin_bottom(8);
+    gesture_box.set_margin_start(10);
+    gesture_box.set_margin_end(10);
+    gesture_box.set_vexpand(true);
+    gesture_box.set_valign(gtk::Align::Center);
+    
+    let input_label = Label::new(Some(&gesture.input));
+    input_label.add_css_class("keybind-combo");
+    input_label.set_xalign(0.0);
+    input_label.set_ellipsize(gtk::pango::EllipsizeMode::End);
+    gesture_box.append(&create_combo_row(pointer::GESTURE_ICON, &input_label));
+    
+    let action_label = Label::new(Some(&gesture.action));
+    action_label.add_css_class("keybind-description");
+    action_label.set_xalign(0.0);
+    action_label.set_ellipsize(gtk::pango::EllipsizeMode::End);
+    gesture_box.append(&action_label);
+    
+    if !gesture.details.is_empty() {
+        let details_label = Label::new(Some(&gesture.details.join(" · ")));
+        details_label.add_css_class("keybind-action");
+        details_label.set_xalign(0.0);
+        details_label.set_ellipsize(gtk::pango::EllipsizeMode::End);
+        gesture_box.append(&details_label);
+    }
+    
+    frame.set_child(Some(&gesture_box));
+    frame
+}
+
+/// Create a FlowBox to arrange keybind cards in a grid
+fn create_keybind_flow() -> FlowBox {
+    let keybind_flow = FlowBox::new();
+    keybind_flow.set_selection_mode(SelectionMode::None);
+    keybind_flow.set_homogeneous(false);
+    keybind_flow.set_column_spacing(10);
+    keybind_flow.set_row_spacing(10);
+    keybind_flow.set_min_children_per_line(1);
+    keybind_flow.set_max_children_per_line(4);
+    keybind_flow.set_activate_on_single_click(false);
+    keybind_flow.set_margin_top(6);
+    keybind_flow.set_margin_bottom(6);
+    keybind_flow.set_valign(gtk::Align::Start);
+    keybind_flow.set_halign(gtk::Align::Fill);
+    keybind_flow.set_hexpand(true);
+    keybind_flow
+}
+
+/// What the keybinds of a section have in common
+#[derive(Debug, Clone, PartialEq)]
+enum SectionKind {
+    /// Global binds of one dispatcher category
+    Category(DispatcherCategory),
+    /// All binds of a submap
+    Submap(String),
+}
+
+/// Keybinds of one section together with their index in the keybind list
+type SectionGroup<'a> = (SectionKind, Vec<(usize, &'a Keybind)>);
+
+/// Group keybinds and add a collapsible section for each group. Global
+/// binds are grouped by dispatcher category, followed by one section per
+/// submap in config order. Gestures go with the mouse binds.
+pub fn populate_keybind_sections(
+    container: &GtkBox,
+    keybinds: &[Keybind],
+    gestures: &[Gesture],
+    actions: &CardActions,
+) -> Vec<KeybindSection> {
+    let mut groups: Vec<SectionGroup> = DispatcherCategory::ALL
+        .iter()
+        .map(|&category| (SectionKind::Category(category), Vec::new()))
+        .collect();
+    
+    for (idx, keybind) in keybinds.iter().enumerate() {
+        let kind = match &keybind.submap {
+            Some(submap) => SectionKind::Submap(submap.clone()),
+            None => SectionKind::Category(keybind.category()),
+        };
+        match groups.iter_mut().find(|(group_kind, _)| *group_kind == kind) {
+            Some((_, group)) => group.push((idx, keybind)),
+            None => groups.push((kind, vec![(idx, keybind)])),
+        }
+    }
+    
+    let mut sections = Vec::new();
+    
+    for (kind, group) in groups {
+        let section_gestures = match kind {
+            SectionKind::Category(DispatcherCategory::Pointer) => gestures,
+            _ => &[],
+        };
+        if group.is_empty() && section_gestures.is_empty() {
+            continue;
+        }
+        
+        // Header with the section name, submaps also list the binds that enter them
+        let (title, subtitle) = match &kind {
+            SectionKind::Category(category) if !section_gestures.is_empty() => (
+                category.name().to_string(),
+                format!("{} keybinds · {} gestures", group.len(), section_gestures.len()),
+            ),
+            SectionKind::Category(category) => (category.name().to_string(), format!("{} keybinds", group.len())),
+            SectionKind::Submap(name) => {
+                let entered_with: Vec<String> = keybinds
+                    .iter()
+                    .filter(|k| k.entered_submap() == Some(name.as_str()))
+                    .map(Keybind::combo_text)
+                    .collect();
+                
+                let subtitle = if entered_with.is_empty() {
+                    format!("Submap · {} keybinds", group.len())
+                } else {
+                    format!("Submap · {} keybinds · entered with {}", group.len(), entered_with.join(", "))
+                };
+                (name.clone(), subtitle)
+            }
+        };
+        
+        let header_box = GtkBox::new(Orientation::Vertical, 0);
+        let title_label = Label::new(Some(&title));
+        title_label.add_css_class("section-title");
+        title_label.set_xalign(0.0);
+        header_box.append(&title_label);
+        
+        let subtitle_label = Label::new(Some(&subtitle));
+        subtitle_label.add_css_class("section-subtitle");
+        subtitle_label.set_xalign(0.0);
+        header_box.append(&subtitle_label);
+        
+        let flow = create_keybind_flow();
+        let mut cards = Vec::with_capacity(group.len());
+        for (idx, keybind) in &group {
+            let flow_child = FlowBoxChild::new();
+            flow_child.set_child(Some(&create_keybind_card(keybind, *idx, actions)));
+            flow.insert(&flow_child, -1);
+            cards.push((*idx, flow_child));
+        }
+        
+        let mut gesture_cards = Vec::with_capacity(section_gestures.len());
+        for gesture in section_gestures {
+            let flow_child = FlowBoxChild::new();
+            flow_child.set_child(Some(&create_gesture_card(gesture)));
+            flow.insert(&flow_child, -1);
+            gesture_cards.push((format!("{} {}", gesture.input, gesture.action), flow_child));
+        }
+        
+        let expander = Expander::new(None);
+        expander.set_label_widget(Some(&header_box));
+        expander.set_expanded(true);
+        expander.set_child(Some(&flow));
+        container.append(&expander);
+        
+        debug!("Added section {:?} with {} keybinds", kind, group.len());
+        sections.push(KeybindSection { expander, flow, cards, gesture_cards });
+    }
+    
+    sections
+}
+
+/// Remove the current sections from the container and add new ones for the keybinds
+pub fn rebuild_keybind_sections(
+    container: &GtkBox,
+    keybinds: &[Keybind],
+    gestures: &[Gesture],
+    actions: &CardActions,
+) -> Vec<KeybindSection> {
+    while let Some(child) = container.first_child() {
+        container.remove(&child);
+    }
+    
+    populate_keybind_sections(container, keybinds, gestures, actions)
+}
+
+/// Filter, rank and highlight the keybind cards for a search query.
+/// Binds removed by an unbind are only shown with `show_removed`.
+/// Sections without any matching card are hidden.
+pub fn search_keybind_sections(sections: &[KeybindSection], keybinds: &[Keybind], query: &Query, show_removed: bool) {
+    for section in sections {
+        let mut ranks = HashMap::new();
+        let mut any_visible = false;
+        
+        for (idx, flow_child) in &section.cards {
+            let score = keybinds
+                .get(*idx)
+                .filter(|keybind| show_removed || keybind.removed_by.is_none())
+                .and_then(|keybind| query.score(keybind));
+            flow_child.set_visible(score.is_some());
+            any_visible |= score.is_some();
+            highlight_card(flow_child, query.terms());
+            
+            // Best matches first, config order among equal scores
+            ranks.insert(flow_child.clone(), (Reverse(score.unwrap_or(0)), *idx));
+        }
+        
+        // Gestures only have text to match, they come after the binds
+        for (text, flow_child) in &section.gesture_cards {
+            let score = query.score_text(text);
+            flow_child.set_visible(score.is_some());
+            any_visible |= score.is_some();
+            highlight_card(flow_child, query.terms());
+            ranks.insert(flow_child.clone(), (Reverse(score.unwrap_or(0)), usize::MAX));
+        }
+        
+        section.flow.set_sort_func(move |a, b| ranks.get(a).cmp(&ranks.get(b)).into());
+        section.expander.set_visible(any_visible);
+    }
+}
+
+/// Show only the cards for which `visible` returns true, called with the
+/// keybind index and the card, and hide sections without any visible card
+pub fn filter_keybind_cards<F>(sections: &[KeybindSection], visible: F)
+where
+    F: Fn(usize, &FlowBoxChild) -> bool,
+{
+    for section in sections {
+        let mut any_visible = false;
+        
+        for (idx, flow_child) in &section.cards {
+            let card_visible = visible(*idx, flow_child);
+            flow_child.set_visible(card_visible);
+            highlight_card(flow_child, &[]);
+            any_visible |= card_visible;
+        }
+        
+        // Gestures have no combo to match
+        for (_, flow_child) in &section.gesture_cards {
+            flow_child.set_visible(false);
+            highlight_card(flow_child, &[]);
+        }
+        
+        section.expander.set_visible(any_visible);
+    }
+}
+
+/// Mark the characters matched by the search terms in the labels of a card
+fn highlight_card(flow_child: &FlowBoxChild, terms: &[String]) {
+    let Some(keybind_box) = flow_child.child().and_then(|frame| frame.first_child()) else {
+        return;
+    };
+    
+    let mut curr = keybind_box.first_child();
+    while let Some(widget) = curr {
+        if let Some(label) = widget.downcast_ref::<Label>() {
+            label.set_markup(&search::highlight_markup(&label.text(), terms));
+        }
+        // The combo of mouse binds and gestures sits next to an icon
+        if widget.has_css_class("keybind-combo-row") {
+            if let Some(label) = widget.last_child().and_downcast::<Label>() {
+                label.set_markup(&search::highlight_markup(&label.text(), terms));
+            }
+        }
+        curr = widget.next_sibling();
+    }
+}
+
+/// Create the collapsible list of keybind conflicts. Each location button
+/// calls `on_jump` with the index of the keybind it refers to.
+pub fn create_conflicts_panel<F>(conflicts: &[Conflict], keybinds: &[Keybind], on_jump: F) -> Expander
+where
+    F: Fn(usize) + Clone + 'static,
+{
+    let header = Label::new(Some(&format!("⚠ {} keybind conflicts", conflicts.len())));
+    header.add_css_class("conflicts-title");
+    
+    let list_box = ListBox::new();
+    list_box.set_selection_mode(SelectionMode::None);
+    list_box.add_css_class("conflicts-list");
+    
+    for conflict in conflicts {
+        let row_box = GtkBox::new(Orientation::Vertical, 4);
+        row_box.set_margin_top(6);
+        row_box.set_margin_bottom(6);
+        row_box.set_margin_start(10);
+        row_box.set_margin_end(10);
+        
+        let message_label = Label::new(Some(&conflict.message(keybinds)));
+        message_label.set_xalign(0.0);
+        message_label.set_wrap(true);
+        row_box.append(&message_label);
+        
+        // One button per conflicting bind that jumps to its card
+        let buttons_box = GtkBox::new(Orientation::Horizontal, 6);
+        for &idx in &conflict.keybinds {
+            let location = keybinds[idx].origin
+                .as_ref()
+                .map(|origin| origin.display_short())
+                .unwrap_or_else(|| keybinds[idx].combo_text());
+            
+            let jump_button = gtk::Button::with_label(&location);
+            jump_button.add_css_class("flat");
+            jump_button.set_tooltip_text(Some("Show this keybind"));
+            
+            let on_jump = on_jump.clone();
+            jump_button.connect_clicked(move |_| on_jump(idx));
+            buttons_box.append(&jump_button);
+        }
+        row_box.append(&buttons_box);
+        
+        let row = ListBoxRow::new();
+        row.set_child(Some(&row_box));
+        list_box.append(&row);
+    }
+    
+    let scrolled_window = ScrolledWindow::new();
+    scrolled_window.set_policy(gtk::PolicyType::Never, gtk::PolicyType::Automatic);
+    scrolled_window.set_max_content_height(150);
+    scrolled_window.set_propagate_natural_height(true);
+    scrolled_window.set_child(Some(&list_box));
+    
+    let expander = Expander::new(None);
+    expander.set_label_widget(Some(&header));
+    expander.set_child(Some(&scrolled_window));
+    expander.add_css_class("conflicts-panel");
+    expander
+}
+
+/// Collapsible list of `source =` lines that could not be resolved
+pub fn create_source_warnings_panel(warnings: &[SourceWarning]) -> Expander {
+    let header = Label::new(Some(&format!("⚠ {} sourced files not found", warnings.len())));
+    header.add_css_class("conflicts-title");
+    
+    let list_box = ListBox::new();
+    list_box.set_selection_mode(SelectionMode::None);
+    list_box.add_css_class("conflicts-list");
+    
+    for warning in warnings {
+        let row_box = GtkBox::new(Orientation::Vertical, 4);
+        row_box.set_margin_top(6);
+        row_box.set_margin_bottom(6);
+        row_box.set_margin_start(10);
+        row_box.set_margin_end(10);
+        
+        let line_label = Label::new(Some(&format!("{}  {}", warning.origin.display_short(), warning.origin.text.trim())));
+        line_label.set_xalign(0.0);
+        line_label.set_wrap(true);
+        line_label.set_selectable(true);
+        line_label.set_tooltip_text(Some(&warning.origin.file.display().to_string()));
+        row_box.append(&line_label);
+        
+        let message_label = Label::new(Some(&warning.message));
+        message_label.set_xalign(0.0);
+        message_label.set_wrap(true);
+        message_label.add_css_class("section-subtitle");
+        row_box.append(&message_label);
+        
+        let row = ListBoxRow::new();
+        row.set_child(Some(&row_box));
+        list_box.append(&row);
+    }
+    
+    let scrolled_window = ScrolledWindow::new();
+    scrolled_window.set_policy(gtk::PolicyType::Never, gtk::PolicyType::Automatic);
+    scrolled_window.set_max_content_height(150);
+    scrolled_window.set_propagate_natural_height(true);
+    scrolled_window.set_child(Some(&list_box));
+    
+    let expander = Expander::new(None);
+    expander.set_label_widget(Some(&header));
+    expander.set_child(Some(&scrolled_window));
+    expander.add_css_class("conflicts-panel");
+    expander
+}
+
+/// Scroll to the card of a keybind and briefly highlight it
+pub fn scroll_to_keybind(scrolled_window: &ScrolledWindow, container: &GtkBox, sections: &[KeybindSection], index: usize) {
+    let Some((section, flow_child)) = sections.iter().find_map(|section| {
+        section.cards
+            .iter()
+            .find(|(idx, _)| *idx == index)
+            .map(|(_, child)| (section, child.clone()))
+    }) else {
+        debug!("No card found for keybind {}", index);
+        return;
+    };
+    
+    section.expander.set_visible(true);
+    section.expander.set_expanded(true);
+    flow_child.set_visible(true);
+    
+    if let Some(card) = flow_child.child() {
+        card.add_css_class("keybind-highlight");
+        gtk::glib::timeout_add_local_once(Duration::from_secs(2), move || {
+            card.remove_css_class("keybind-highlight");
+        });
+    }
+    
+    // Wait for the layout so an expanded section has its final size
+    let scrolled_window = scrolled_window.clone();
+    let container = container.clone();
+    gtk::glib::idle_add_local_once(move || {
+        if let Some(point) = flow_child.compute_point(&container, &gtk::graphene::Point::new(0.0, 0.0)) {
+            scrolled_window.vadjustment().set_value((point.y() - 20.0).max(0.0) as f64);
+        }
+    });
+}
+
+/// Placeholder shown instead of the keybinds when no config file was found
+pub fn create_empty_state(tried_paths: &[PathBuf]) -> GtkBox {
+    let empty_box = GtkBox::new(Orientation::Vertical, 8);
+    empty_box.set_valign(gtk::Align::Center);
+    empty_box.set_vexpand(true);
+    empty_box.add_css_class("empty-state");
+    
+    let icon = gtk::Image::from_icon_name("dialog-question-symbolic");
+    icon.set_pixel_size(48);
+    empty_box.append(&icon);
+    
+    let title = Label::new(Some("No Hyprland config found"));
+    title.add_css_class("section-title");
+    empty_box.append(&title);
+    
+    let hint = Label::new(Some("Looked for the config file in these places:"));
+    hint.add_css_class("section-subtitle");
+    empty_box.append(&hint);
+    
+    for path in tried_paths {
+        let path_label = Label::new(Some(&path.display().to_string()));
+        path_label.add_css_class("empty-state-path");
+        path_label.set_selectable(true);
+        empty_box.append(&path_label);
+    }
+    
+    let usage = Label::new(Some("Start with --config <FILE> or set HYPRLAND_CONFIG to use another file"));
+    usage.add_css_class("section-subtitle");
+    usage.set_wrap(true);
+    empty_box.append(&usage);
+    
+    empty_box
+}
+
+/// Show a modal error message
+pub fn show_error(window: &impl IsA<gtk::Window>, title: &str, details: &str) {
+    let message = gtk::MessageDialog::builder()
+        .transient_for(window)
+        .modal(true)
+        .message_type(gtk::MessageType::Error)
+        .buttons(gtk::ButtonsType::Ok)
+        .text(title)
+        .secondary_text(details)
+        .build();
+    message.connect_response(|message, _| message.close());
+    message.present();
+}
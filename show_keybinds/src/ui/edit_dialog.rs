@@ -0,0 +1,142 @@
+use anyhow::Result;
+use gtk::prelude::*;
+use gtk::{Box as GtkBox, Entry, Grid, Label, Orientation};
+use log::error;
+use std::path::{Path, PathBuf};
+
+use crate::config_parser::Origin;
+use crate::editor::BindLine;
+
+/// Add a labelled entry to the next row of the grid
+fn add_field(grid: &Grid, row: i32, title: &str, value: &str, placeholder: &str) -> Entry {
+    let label = Label::new(Some(title));
+    label.set_xalign(1.0);
+    
+    let entry = Entry::new();
+    entry.set_text(value);
+    entry.set_placeholder_text(Some(placeholder));
+    entry.set_hexpand(true);
+    
+    grid.attach(&label, 0, row, 1, 1);
+    grid.attach(&entry, 1, row, 1, 1);
+    entry
+}
+
+/// Show a dialog to edit the bind at `origin`, or to add a new bind when
+/// `origin` is `None`. New binds are appended to one of `files`.
+///
+/// `on_save` writes the bind; when it fails the error is shown in the
+/// dialog and the dialog stays open.
+pub fn show_edit_dialog<F>(parent: &impl IsA<gtk::Window>, origin: Option<&Origin>, files: &[PathBuf], on_save: F)
+where
+    F: Fn(&BindLine, Option<&Path>) -> Result<()> + 'static,
+{
+    let bind = origin
+        .and_then(|origin| BindLine::parse(&origin.text))
+        .unwrap_or_else(|| BindLine { keyword: "bind".to_string(), ..Default::default() });
+    
+    let dialog = gtk::Window::builder()
+        .transient_for(parent)
+        .modal(true)
+        .title(if origin.is_some() { "Edit keybind" } else { "Add keybind" })
+        .default_width(480)
+        .build();
+    
+    let content_box = GtkBox::new(Orientation::Vertical, 12);
+    content_box.set_margin_top(16);
+    content_box.set_margin_bottom(16);
+    content_box.set_margin_start(16);
+    content_box.set_margin_end(16);
+    
+    if let Some(origin) = origin {
+        let location_label = Label::new(Some(&origin.display_short()));
+        location_label.add_css_class("section-subtitle");
+        location_label.set_xalign(0.0);
+        location_label.set_tooltip_text(Some(&origin.file.display().to_string()));
+        content_box.append(&location_label);
+    }
+    
+    let grid = Grid::new();
+    grid.set_row_spacing(8);
+    grid.set_column_spacing(12);
+    
+    let keyword_entry = add_field(&grid, 0, "Keyword", &bind.keyword, "bind, binde, bindd...");
+    let modifiers_entry = add_field(&grid, 1, "Modifiers", &bind.modifiers, "SUPER SHIFT");
+    let key_entry = add_field(&grid, 2, "Key", &bind.key, "Q");
+    let description_entry = add_field(&grid, 3, "Description", &bind.description, "Only used by bindd");
+    let dispatcher_entry = add_field(&grid, 4, "Dispatcher", &bind.dispatcher, "exec");
+    let args_entry = add_field(&grid, 5, "Arguments", &bind.args, "kitty");
+    
+    // New binds need a file to be written to
+    let file_dropdown = if origin.is_none() {
+        let names: Vec<String> = files.iter().map(|file| file.display().to_string()).collect();
+        let names: Vec<&str> = names.iter().map(String::as_str).collect();
+        let dropdown = gtk::DropDown::from_strings(&names);
+        
+        let label = Label::new(Some("File"));
+        label.set_xalign(1.0);
+        grid.attach(&label, 0, 6, 1, 1);
+        grid.attach(&dropdown, 1, 6, 1, 1);
+        Some(dropdown)
+    } else {
+        None
+    };
+    content_box.append(&grid);
+    
+    let error_label = Label::new(None);
+    error_label.add_css_class("edit-error");
+    error_label.set_xalign(0.0);
+    error_label.set_wrap(true);
+    error_label.set_visible(false);
+    content_box.append(&error_label);
+    
+    // Cancel and save buttons aligned to the right
+    let buttons_box = GtkBox::new(Orientation::Horizontal, 8);
+    buttons_box.set_halign(gtk::Align::End);
+    let cancel_button = gtk::Button::with_label("Cancel");
+    let save_button = gtk::Button::with_label("Save");
+    save_button.add_css_class("suggested-action");
+    buttons_box.append(&cancel_button);
+    buttons_box.append(&save_button);
+    content_box.append(&buttons_box);
+    
+    let dialog_weak = dialog.downgrade();
+    cancel_button.connect_clicked(move |_| {
+        if let Some(dialog) = dialog_weak.upgrade() {
+            dialog.close();
+        }
+    });
+    
+    let files = files.to_vec();
+    let dialog_weak = dialog.downgrade();
+    save_button.connect_clicked(move |_| {
+        let bind = BindLine {
+            keyword: keyword_entry.text().trim().to_string(),
+            modifiers: modifiers_entry.text().to_string(),
+            key: key_entry.text().to_string(),
+            description: description_entry.text().to_string(),
+            dispatcher: dispatcher_entry.text().to_string(),
+            args: args_entry.text().to_string(),
+        };
+        let file = file_dropdown
+            .as_ref()
+            .and_then(|dropdown| files.get(dropdown.selected() as usize))
+            .map(PathBuf::as_path);
+        
+        match on_save(&bind, file) {
+            Ok(()) => {
+                if let Some(dialog) = dialog_weak.upgrade() {
+                    dialog.close();
+                }
+            }
+            Err(e) => {
+                error!("Failed to save keybind: {:#}", e);
+                error_label.set_text(&format!("{:#}", e));
+                error_label.set_visible(true);
+            }
+        }
+    });
+    
+    dialog.set_child(Some(&content_box));
+    dialog.present();
+}
@@ -0,0 +1,156 @@
+use gtk::gio::{self, prelude::*};
+use gtk::glib;
+use log::{debug, error};
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::Duration;
+
+/// Editors often write a file in several steps, wait for them to finish
+const RELOAD_DELAY: Duration = Duration::from_millis(300);
+
+/// Watches config files and calls back once after a burst of changes
+pub struct ConfigWatcher {
+    monitors: RefCell<Vec<gio::FileMonitor>>,
+    /// Pending callback of the current burst of changes
+    pending: Rc<RefCell<Option<glib::SourceId>>>,
+}
+
+impl ConfigWatcher {
+    pub fn new() -> Self {
+        Self {
+            monitors: RefCell::new(Vec::new()),
+            pending: Rc::new(RefCell::new(None)),
+        }
+    }
+    
+    /// Replace the watched files. The directories are monitored instead of
+    /// the files themselves so files replaced by a rename are still noticed.
+    /// Files matching one of `patterns` (glob sources and missing files) are
+    /// watched for as well, so adding them to the config triggers a reload.
+    /// Paths are compared with symlinks and `..` resolved, and a symlinked
+    /// file is watched both where the link and where its target is.
+    pub fn watch<F: Fn() + 'static>(&self, files: &[PathBuf], patterns: &[PathBuf], on_change: F) {
+        for monitor in self.monitors.borrow_mut().drain(..) {
+            monitor.cancel();
+        }
+        
+        let on_change = Rc::new(on_change);
+        let file_count = files.len();
+        let mut files: Vec<PathBuf> = files.iter().flat_map(|file| [file.clone(), canonical(file)]).collect();
+        files.sort();
+        files.dedup();
+        let files = Rc::new(files);
+        let patterns: Rc<Vec<(PathBuf, glob::Pattern)>> = Rc::new(
+            patterns
+                .iter()
+                .map(|pattern| canonical_pattern(pattern))
+                .filter_map(|pattern| {
+                    let matcher = glob::Pattern::new(&pattern.to_string_lossy()).ok()?;
+                    Some((pattern, matcher))
+                })
+                .collect(),
+        );
+        let mut directories: Vec<PathBuf> = files
+            .iter()
+            .filter_map(|file| file.parent().map(PathBuf::from))
+            .chain(patterns.iter().filter_map(|(pattern, _)| pattern_directory(pattern)))
+            .collect();
+        directories.sort();
+        directories.dedup();
+        
+        let mut monitors = Vec::new();
+        for directory in directories {
+            let monitor = match gio::File::for_path(&directory)
+                .monitor_directory(gio::FileMonitorFlags::WATCH_MOVES, gio::Cancellable::NONE)
+            {
+                Ok(monitor) => monitor,
+                Err(e) => {
+                    error!("Failed to watch {:?}: {}", directory, e);
+                    continue;
+                }
+            };
+            
+            let files = files.clone();
+            let patterns = patterns.clone();
+            let on_change = on_change.clone();
+            let pending = self.pending.clone();
+            monitor.connect_changed(move |_, file, other_file, event| {
+                if !matches!(
+                    event,
+                    gio::FileMonitorEvent::Changed
+                        | gio::FileMonitorEvent::ChangesDoneHint
+                        | gio::FileMonitorEvent::Created
+                        | gio::FileMonitorEvent::Deleted
+                        | gio::FileMonitorEvent::Renamed
+                        | gio::FileMonitorEvent::MovedIn
+                        | gio::FileMonitorEvent::MovedOut
+                ) {
+                    return;
+                }
+                
+                // Renames report the new name as the other file. A new directory
+                // on the way to a missing source counts too, it is watched next.
+                let touched = [Some(file), other_file]
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|file| file.path())
+                    .map(|path| canonical(&path))
+                    .any(|path| {
+                        files.contains(&path)
+                            || patterns.iter().any(|(pattern, matcher)| {
+                                matcher.matches_path(&path) || (pattern.starts_with(&path) && path.is_dir())
+                            })
+                    });
+                if !touched {
+                    return;
+                }
+                debug!("Config file changed: {:?} ({:?})", file.path(), event);
+                
+                // Restart the delay on every change so a burst causes one reload
+                if let Some(source) = pending.borrow_mut().take() {
+                    source.remove();
+                }
+                let on_change = on_change.clone();
+                let finished = pending.clone();
+                let source = glib::timeout_add_local_once(RELOAD_DELAY, move || {
+                    finished.borrow_mut().take();
+                    on_change();
+                });
+                *pending.borrow_mut() = Some(source);
+            });
+            monitors.push(monitor);
+        }
+        
+        debug!(
+            "Watching {} config files and {} source patterns in {} directories",
+            file_count, patterns.len(), monitors.len()
+        );
+        *self.monitors.borrow_mut() = monitors;
+    }
+}
+
+/// The path with symlinks and `..` resolved, or the path as it is when it
+/// cannot be resolved, e.g. because the file was deleted
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// A source pattern with its directory resolved, when that directory exists
+fn canonical_pattern(pattern: &Path) -> PathBuf {
+    match (pattern.parent().and_then(|parent| parent.canonicalize().ok()), pattern.file_name()) {
+        (Some(parent), Some(file_name)) => parent.join(file_name),
+        _ => pattern.to_path_buf(),
+    }
+}
+
+/// The existing directory closest to the files a pattern can match: the
+/// parent of a missing file, or the part of a glob before its first wildcard
+fn pattern_directory(pattern: &Path) -> Option<PathBuf> {
+    let fixed: PathBuf = pattern
+        .parent()?
+        .components()
+        .take_while(|component| !component.as_os_str().to_string_lossy().contains(['*', '?', '[']))
+        .collect();
+    fixed.ancestors().find(|dir| dir.is_dir()).map(PathBuf::from)
+}